@@ -0,0 +1,151 @@
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgb, RgbImage};
+
+use crate::chart::{fill_rect, level_color, AXIS, BACKGROUND, TIER_RATINGS};
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::solved::{ClassDecoration, User};
+
+const TEXT: Rgb<u8> = Rgb([45, 45, 45]);
+const SUBTEXT: Rgb<u8> = Rgb([120, 120, 120]);
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 220;
+const MARGIN: u32 = 30;
+const AVATAR_SIZE: u32 = 160;
+
+fn tier_label(tier: u64) -> String {
+    const GROUPS: [&str; 6] = ["Bronze", "Silver", "Gold", "Platinum", "Diamond", "Ruby"];
+    match tier {
+        1..=30 => format!(
+            "{} {}",
+            GROUPS[(tier as usize - 1) / 5],
+            ["V", "IV", "III", "II", "I"][(tier as usize - 1) % 5]
+        ),
+        31 => "Master".to_string(),
+        _ => "Unrated".to_string(),
+    }
+}
+
+/// Returns the rating at which the tier starts and the one of the next tier, if any.
+fn tier_range(tier: u64) -> (u64, Option<u64>) {
+    match tier {
+        0 => (0, Some(TIER_RATINGS[0])),
+        1..=30 => (
+            TIER_RATINGS[tier as usize - 1],
+            Some(TIER_RATINGS[tier as usize]),
+        ),
+        _ => (TIER_RATINGS[30], None),
+    }
+}
+
+/// Rendered cards change with any of these, so they make up the key of cached file ids.
+pub fn cache_key(user: &User) -> String {
+    format!(
+        "card:{}:{}:{}:{}:{}:{}",
+        user.handle,
+        user.rating,
+        user.class,
+        user.class_decoration as u8,
+        user.solved_count,
+        user.rank
+    )
+}
+
+/// Draws the avatar, tier badge, rating bar toward the next tier, class, rank and solved count.
+pub fn render_profile_card(user: &User, avatar: Option<&DynamicImage>) -> RgbImage {
+    let mut image = RgbImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+    let color = level_color(user.tier);
+    fill_rect(&mut image, 0, 0, 8, HEIGHT, color);
+
+    let avatar_y = (HEIGHT - AVATAR_SIZE) / 2;
+    match avatar {
+        Some(avatar) => {
+            let avatar = imageops::resize(
+                &avatar.to_rgb8(),
+                AVATAR_SIZE,
+                AVATAR_SIZE,
+                FilterType::Triangle,
+            );
+            imageops::replace(&mut image, &avatar, MARGIN, avatar_y);
+        }
+        None => fill_rect(&mut image, MARGIN, avatar_y, AVATAR_SIZE, AVATAR_SIZE, AXIS),
+    }
+
+    let left = MARGIN * 2 + AVATAR_SIZE;
+    let right = WIDTH - MARGIN;
+
+    // The tier badge sits in the top right corner, so the handle shrinks to leave room for it
+    let badge_size = 48;
+    let badge_x = right - badge_size;
+    fill_rect(&mut image, badge_x, MARGIN, badge_size, badge_size, color);
+    let numeral = match user.tier {
+        1..=30 => ["5", "4", "3", "2", "1"][(user.tier as usize - 1) % 5],
+        31 => "M",
+        _ => "?",
+    };
+    font::draw_text(
+        &mut image,
+        badge_x + (badge_size - font::text_width(numeral, 4)) / 2,
+        MARGIN + (badge_size - GLYPH_HEIGHT * 4) / 2,
+        numeral,
+        4,
+        BACKGROUND,
+    );
+
+    let handle_room = badge_x - left - 10;
+    let scale = (1..=4)
+        .rev()
+        .find(|&scale| font::text_width(&user.handle, scale) <= handle_room)
+        .unwrap_or(1);
+    font::draw_text(&mut image, left, MARGIN, &user.handle, scale, TEXT);
+
+    font::draw_text(
+        &mut image,
+        left,
+        MARGIN + 50,
+        &format!("{} {}", tier_label(user.tier), user.rating),
+        2,
+        color,
+    );
+
+    let bar_y = MARGIN + 78;
+    let bar_width = right - left;
+    let (lower, next) = tier_range(user.tier);
+    let filled = match next {
+        Some(next) => {
+            let progress = user.rating.saturating_sub(lower).min(next - lower);
+            (progress * bar_width as u64 / (next - lower)) as u32
+        }
+        None => bar_width,
+    };
+    fill_rect(&mut image, left, bar_y, bar_width, 12, AXIS);
+    fill_rect(&mut image, left, bar_y, filled, 12, color);
+    let progress = match next {
+        Some(next) => format!(
+            "{} to {}",
+            next.saturating_sub(user.rating),
+            tier_label(user.tier + 1)
+        ),
+        None => "Max tier".to_string(),
+    };
+    font::draw_text(&mut image, left, bar_y + 20, &progress, 2, SUBTEXT);
+
+    let decoration = match user.class_decoration {
+        ClassDecoration::None => "",
+        ClassDecoration::Silver => "+",
+        ClassDecoration::Gold => "++",
+    };
+    font::draw_text(
+        &mut image,
+        left,
+        HEIGHT - MARGIN - GLYPH_HEIGHT * 2,
+        &format!(
+            "Class {}{}  #{}  {} solved",
+            user.class, decoration, user.rank, user.solved_count
+        ),
+        2,
+        TEXT,
+    );
+
+    image
+}
@@ -0,0 +1,237 @@
+use image::png::PngEncoder;
+use image::{ColorType, ImageResult, Rgb, RgbImage};
+
+use crate::font;
+use crate::solved::ProblemStat;
+
+pub const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+pub const AXIS: Rgb<u8> = Rgb([200, 200, 200]);
+
+/// Colors of Unrated, Bronze, Silver, Gold, Platinum, Diamond, Ruby and Master, as on solved.ac.
+const TIER_COLORS: [Rgb<u8>; 8] = [
+    Rgb([45, 45, 45]),
+    Rgb([173, 86, 0]),
+    Rgb([67, 95, 122]),
+    Rgb([236, 154, 0]),
+    Rgb([39, 226, 164]),
+    Rgb([0, 180, 252]),
+    Rgb([255, 0, 98]),
+    Rgb([179, 0, 255]),
+];
+
+/// Minimum ratings of Bronze V to Master.
+pub const TIER_RATINGS: [u64; 31] = [
+    30, 60, 90, 120, 150, 200, 300, 400, 500, 650, 800, 950, 1100, 1250, 1400, 1600, 1750, 1900,
+    2000, 2100, 2200, 2300, 2400, 2500, 2600, 2700, 2800, 2850, 2900, 2950, 3000,
+];
+
+pub fn level_color(level: u64) -> Rgb<u8> {
+    TIER_COLORS[(level as usize).div_ceil(5).min(TIER_COLORS.len() - 1)]
+}
+
+pub fn encode_png(image: &RgbImage) -> ImageResult<Vec<u8>> {
+    let mut png = vec![];
+    PngEncoder::new(&mut png).encode(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ColorType::Rgb8,
+    )?;
+    Ok(png)
+}
+
+pub fn fill_rect(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    let right = (x + width).min(image.width());
+    let bottom = (y + height).min(image.height());
+    for py in y..bottom {
+        for px in x..right {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// Draws one bar per level from Unrated to Ruby I, scaled to the most solved level.
+pub fn render_level_histogram(stats: &[ProblemStat]) -> RgbImage {
+    const BAR_WIDTH: u32 = 20;
+    const GAP: u32 = 4;
+    const MARGIN: u32 = 20;
+    const CHART_HEIGHT: u32 = 300;
+
+    let width = MARGIN * 2 + 31 * (BAR_WIDTH + GAP) - GAP;
+    let height = MARGIN * 2 + CHART_HEIGHT;
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+
+    let max = stats
+        .iter()
+        .map(|stat| stat.solved)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for stat in stats.iter().filter(|stat| stat.level <= 30) {
+        let bar_height = (stat.solved * CHART_HEIGHT as u64 / max) as u32;
+        let x = MARGIN + stat.level as u32 * (BAR_WIDTH + GAP);
+        let y = MARGIN + CHART_HEIGHT - bar_height;
+        fill_rect(
+            &mut image,
+            x,
+            y,
+            BAR_WIDTH,
+            bar_height,
+            level_color(stat.level),
+        );
+    }
+    fill_rect(
+        &mut image,
+        MARGIN,
+        MARGIN + CHART_HEIGHT,
+        width - MARGIN * 2,
+        1,
+        AXIS,
+    );
+
+    image
+}
+
+fn lighten(color: Rgb<u8>) -> Rgb<u8> {
+    let Rgb([r, g, b]) = color;
+    Rgb([
+        r + (255 - r) / 4 * 3,
+        g + (255 - g) / 4 * 3,
+        b + (255 - b) / 4 * 3,
+    ])
+}
+
+/// Formats a Unix timestamp in milliseconds as a UTC date.
+fn format_date(millis: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = (millis / 86_400_000) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{}-{:02}-{:02}", year, month, day)
+}
+
+/// Draws the rating as a step line from `from` to `to`, over bands colored by tier. `history`
+/// holds `(timestamp, rating)` pairs in order, and points before `from` are drawn at `from`.
+pub fn render_rating_history(history: &[(u64, u64)], from: u64, to: u64) -> RgbImage {
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = 400;
+    const LEFT: u32 = 60;
+    const RIGHT: u32 = 20;
+    const TOP: u32 = 20;
+    const BOTTOM: u32 = 40;
+    const LINE: Rgb<u8> = Rgb([45, 45, 45]);
+    const LABEL: Rgb<u8> = Rgb([120, 120, 120]);
+
+    let mut image = RgbImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+
+    let min = history.iter().map(|&(_, rating)| rating).min().unwrap_or(0);
+    let max = history.iter().map(|&(_, rating)| rating).max().unwrap_or(0);
+    let padding = ((max - min) / 10).max(20);
+    let low = min.saturating_sub(padding);
+    let high = max + padding;
+    let y_of = |rating: u64| {
+        TOP + ((high - rating.clamp(low, high)) * plot_height as u64 / (high - low)) as u32
+    };
+    let span = to.saturating_sub(from).max(1);
+    let x_of = |at: u64| LEFT + ((at.clamp(from, to) - from) * plot_width as u64 / span) as u32;
+
+    // Tier groups start at Bronze V, Silver V, ..., Master
+    let mut bounds = vec![0];
+    bounds.extend(TIER_RATINGS.iter().step_by(5));
+    bounds.push(u64::MAX);
+    for (i, range) in bounds.windows(2).enumerate() {
+        if range[1] <= low || range[0] >= high {
+            continue;
+        }
+        let top = y_of(range[1].min(high));
+        let bottom = y_of(range[0].max(low));
+        fill_rect(
+            &mut image,
+            LEFT,
+            top,
+            plot_width,
+            bottom - top,
+            lighten(TIER_COLORS[i]),
+        );
+    }
+    // Label the tier thresholds in range, skipping those that would overlap the previous label
+    let mut last_label = u32::MAX;
+    for &threshold in TIER_RATINGS
+        .iter()
+        .filter(|&&rating| low < rating && rating < high)
+    {
+        let y = y_of(threshold);
+        if last_label.saturating_sub(y) < 14 {
+            continue;
+        }
+        last_label = y;
+        fill_rect(&mut image, LEFT - 4, y, 4, 1, LABEL);
+        let label = threshold.to_string();
+        font::draw_text(
+            &mut image,
+            LEFT - 8 - font::text_width(&label, 1),
+            y - font::GLYPH_HEIGHT / 2,
+            &label,
+            1,
+            LABEL,
+        );
+    }
+
+    for (i, &(at, rating)) in history.iter().enumerate() {
+        let x = x_of(at);
+        let y = y_of(rating);
+        let next_x = history.get(i + 1).map_or(x_of(to), |&(at, _)| x_of(at));
+        fill_rect(&mut image, x, y.saturating_sub(1), next_x - x + 1, 3, LINE);
+        if let Some(&(_, next_rating)) = history.get(i + 1) {
+            let next_y = y_of(next_rating);
+            let (top, bottom) = (y.min(next_y), y.max(next_y));
+            fill_rect(
+                &mut image,
+                next_x.saturating_sub(1),
+                top,
+                3,
+                bottom - top + 1,
+                LINE,
+            );
+        }
+        fill_rect(
+            &mut image,
+            x.saturating_sub(3),
+            y.saturating_sub(3),
+            7,
+            7,
+            LINE,
+        );
+    }
+    if let Some(&(_, rating)) = history.last() {
+        let label = rating.to_string();
+        let y = y_of(rating);
+        let x = (x_of(to) - font::text_width(&label, 2)).max(LEFT);
+        let y = if y >= TOP + 20 { y - 20 } else { y + 8 };
+        font::draw_text(&mut image, x, y, &label, 2, LINE);
+    }
+
+    fill_rect(&mut image, LEFT, TOP + plot_height, plot_width, 1, AXIS);
+    let start = format_date(from);
+    let end = format_date(to);
+    let label_y = TOP + plot_height + 10;
+    font::draw_text(&mut image, LEFT, label_y, &start, 2, LINE);
+    font::draw_text(
+        &mut image,
+        WIDTH - RIGHT - font::text_width(&end, 2),
+        label_y,
+        &end,
+        2,
+        LINE,
+    );
+
+    image
+}
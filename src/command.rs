@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 pub struct Args<'a> {
     inner: &'a str,
 }
@@ -44,3 +47,45 @@ impl<'a> Command<'a> {
         Args { inner: self.rest }
     }
 }
+
+/// Parses `HH:MM` into minutes after midnight.
+pub fn parse_time(time: &str) -> Option<u64> {
+    let (hour, minute) = time.split_once(':')?;
+    let (hour, minute) = (hour.parse::<u64>().ok()?, minute.parse::<u64>().ok()?);
+    if hour < 24 && minute < 60 {
+        Some(hour * 60 + minute)
+    } else {
+        None
+    }
+}
+
+/// Parses periods such as `30d`, `12w`, `6m` and `1y` into milliseconds, rejecting those too
+/// long to count.
+pub fn parse_period(period: &str) -> Option<u64> {
+    let days = match period.chars().last()? {
+        'd' => 1,
+        'w' => 7,
+        'm' => 30,
+        'y' => 365,
+        _ => return None,
+    };
+    let count: u64 = period[..period.len() - 1].parse().ok()?;
+    count.checked_mul(days * 24 * 60 * 60 * 1000)
+}
+
+/// Solved steps are positive counts.
+pub fn parse_step(step: &str) -> Option<u64> {
+    step.parse().ok().filter(|&step| step > 0)
+}
+
+/// Expands `for:<handle>` into a filter for problems that the handle has not solved yet, where a
+/// bare `for:` stands for the linked handle.
+pub fn random_query<'a>(args: impl Iterator<Item = &'a str>, linked: Option<&str>) -> String {
+    args.map(|arg| match arg.strip_prefix("for:") {
+        Some("") => linked.map_or_else(|| arg.to_string(), |handle| format!("-s@{}", handle)),
+        Some(handle) => format!("-s@{}", handle),
+        None => arg.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
@@ -0,0 +1,80 @@
+use super::*;
+
+const DAY: u64 = 24 * 60 * 60 * 1000;
+
+#[test]
+fn splits_label_username_and_args() {
+    let command = Command::new("/graph@solvedacbot  kiwiyou   30d");
+    assert_eq!(command.label, "/graph");
+    assert_eq!(command.username, Some("solvedacbot"));
+    assert_eq!(command.args().collect::<Vec<_>>(), ["kiwiyou", "30d"]);
+}
+
+#[test]
+fn parses_times_of_the_day() {
+    assert_eq!(parse_time("00:00"), Some(0));
+    assert_eq!(parse_time("9:05"), Some(9 * 60 + 5));
+    assert_eq!(parse_time("23:59"), Some(23 * 60 + 59));
+}
+
+#[test]
+fn rejects_out_of_range_times() {
+    assert_eq!(parse_time("24:00"), None);
+    assert_eq!(parse_time("12:60"), None);
+    assert_eq!(parse_time("-1:00"), None);
+    assert_eq!(parse_time("1200"), None);
+    assert_eq!(parse_time("12:"), None);
+}
+
+#[test]
+fn parses_periods() {
+    assert_eq!(parse_period("30d"), Some(30 * DAY));
+    assert_eq!(parse_period("12w"), Some(12 * 7 * DAY));
+    assert_eq!(parse_period("6m"), Some(6 * 30 * DAY));
+    assert_eq!(parse_period("1y"), Some(365 * DAY));
+}
+
+#[test]
+fn rejects_invalid_periods() {
+    assert_eq!(parse_period(""), None);
+    assert_eq!(parse_period("d"), None);
+    assert_eq!(parse_period("30"), None);
+    assert_eq!(parse_period("30h"), None);
+    assert_eq!(parse_period("-3d"), None);
+    assert_eq!(parse_period("3일"), None);
+    assert_eq!(parse_period("kiwiyou"), None);
+}
+
+#[test]
+fn rejects_overflowing_periods() {
+    assert_eq!(parse_period("99999999999999999y"), None);
+    assert_eq!(parse_period("99999999999999999999d"), None);
+    assert!(parse_period("213503982y").is_some());
+}
+
+#[test]
+fn parses_positive_steps() {
+    assert_eq!(parse_step("100"), Some(100));
+    assert_eq!(parse_step("0"), None);
+    assert_eq!(parse_step("-100"), None);
+    assert_eq!(parse_step("kiwiyou"), None);
+}
+
+#[test]
+fn expands_for_into_unsolved_filters() {
+    let args = ["*g5..g1", "for:kiwiyou", "#dp"];
+    assert_eq!(
+        random_query(args.iter().copied(), None),
+        "*g5..g1 -s@kiwiyou #dp"
+    );
+}
+
+#[test]
+fn expands_bare_for_with_the_linked_handle() {
+    let args = ["for:", "*s"];
+    assert_eq!(
+        random_query(args.iter().copied(), Some("kiwiyou")),
+        "-s@kiwiyou *s"
+    );
+    assert_eq!(random_query(args.iter().copied(), None), "for: *s");
+}
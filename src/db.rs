@@ -1,61 +1,444 @@
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use worker::kv::{KvError, KvStore, KvValue};
 
-pub struct RatingAlarms {
+use crate::i18n::Language;
+use crate::solved::{Cache, Problem, User};
+
+#[cfg(test)]
+mod tests;
+
+/// Subscriptions keyed by `{chat}:{lowercased handle}`, so a chat can follow many users.
+pub struct Subscriptions {
     store: KvStore,
 }
 
+/// What a chat follows of a user, along with the values last seen by the poll.
 #[derive(Serialize, Deserialize)]
-pub struct RatingSubscription {
+pub struct Subscription {
     pub target: String,
+    /// Missing in subscriptions made when only ratings could be watched.
+    #[serde(default = "Watch::ratings")]
+    pub watch: Watch,
     pub rating: u64,
+    /// Missing in subscriptions made before arena ratings were watched.
+    #[serde(default)]
+    pub arena_rating: Option<u64>,
+    #[serde(default)]
+    pub tier: Option<u64>,
+    #[serde(default)]
+    pub class: Option<u64>,
+    /// Stored as `ClassDecoration as u8`, which orders plain, silver and gold.
+    #[serde(default)]
+    pub class_decoration: Option<u8>,
+    #[serde(default)]
+    pub solved_count: Option<u64>,
+    /// Problem ids of the top 100, kept only while new solves are watched.
+    #[serde(default)]
+    pub top_100: Option<Vec<u32>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct Watch {
+    /// Ratings and arena ratings.
+    #[serde(default)]
+    pub ratings: bool,
+    /// Tier promotions and demotions.
+    #[serde(default)]
+    pub tier: bool,
+    /// Classes and their decorations.
+    #[serde(default)]
+    pub class: bool,
+    /// Notifies whenever the solved count reaches a multiple of this.
+    #[serde(default)]
+    pub solved_step: Option<u64>,
+    /// Newly solved problems.
+    #[serde(default)]
+    pub problems: bool,
+}
+
+impl Watch {
+    fn ratings() -> Self {
+        Self {
+            ratings: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.ratings && !self.tier && !self.class && self.solved_step.is_none() && !self.problems
+    }
+}
+
+/// What a poll found for a subscription, in the order the notifications go out.
+pub enum Change<'a> {
+    Rating {
+        prev: u64,
+    },
+    ArenaRating {
+        prev: u64,
+    },
+    Tier {
+        prev: u64,
+    },
+    /// A higher class or decoration; drops are not worth a notification.
+    Class,
+    /// The multiple of the solved step the solved count has reached.
+    Solved(u64),
+    /// Problems entering the top 100, and how many other problems were solved.
+    NewSolves {
+        solves: Vec<&'a Problem>,
+        more: u64,
+    },
+}
+
+impl Subscription {
+    /// Starts a subscription that watches nothing yet from the current state of the user.
+    pub fn new(user: &User) -> Self {
+        let mut subscription = Self {
+            target: user.handle.clone(),
+            watch: Watch::default(),
+            rating: user.rating,
+            arena_rating: None,
+            tier: None,
+            class: None,
+            class_decoration: None,
+            solved_count: None,
+            top_100: None,
+        };
+        subscription.update(user);
+        subscription
+    }
+
+    /// Compares the values last seen with the current ones for what the subscription watches.
+    /// New solves need the top 100 seen last time, so the first poll of them finds nothing.
+    pub fn changes<'a>(&self, user: &User, top_100: Option<&'a [Problem]>) -> Vec<Change<'a>> {
+        let watch = self.watch;
+        let mut changes = vec![];
+        if watch.ratings {
+            if self.rating != user.rating {
+                changes.push(Change::Rating { prev: self.rating });
+            }
+            match self.arena_rating {
+                Some(prev) if prev != user.arena_rating => {
+                    changes.push(Change::ArenaRating { prev });
+                }
+                _ => {}
+            }
+        }
+        if watch.tier {
+            match self.tier {
+                Some(prev) if prev != user.tier => changes.push(Change::Tier { prev }),
+                _ => {}
+            }
+        }
+        if watch.class {
+            if let (Some(class), Some(decoration)) = (self.class, self.class_decoration) {
+                if (user.class, user.class_decoration as u8) > (class, decoration) {
+                    changes.push(Change::Class);
+                }
+            }
+        }
+        if let (Some(step), Some(prev)) = (watch.solved_step, self.solved_count) {
+            if user.solved_count / step > prev / step {
+                changes.push(Change::Solved(user.solved_count / step * step));
+            }
+        }
+        if let (Some(prev), Some(top_100), Some(prev_count)) =
+            (&self.top_100, top_100, self.solved_count)
+        {
+            if user.solved_count > prev_count {
+                // Problems entering the top 100 can only be new solves
+                let solves = top_100
+                    .iter()
+                    .filter(|problem| !prev.contains(&problem.problem_id))
+                    .collect::<Vec<_>>();
+                let more = (user.solved_count - prev_count).saturating_sub(solves.len() as u64);
+                changes.push(Change::NewSolves { solves, more });
+            }
+        }
+        changes
+    }
+
+    /// Records the current values of the user, returning whether any of them changed.
+    pub fn update(&mut self, user: &User) -> bool {
+        let updated = Self {
+            target: self.target.clone(),
+            watch: self.watch,
+            rating: user.rating,
+            arena_rating: Some(user.arena_rating),
+            tier: Some(user.tier),
+            class: Some(user.class),
+            class_decoration: Some(user.class_decoration as u8),
+            solved_count: Some(user.solved_count),
+            top_100: self.top_100.take(),
+        };
+        let changed = self.rating != updated.rating
+            || self.arena_rating != updated.arena_rating
+            || self.tier != updated.tier
+            || self.class != updated.class
+            || self.class_decoration != updated.class_decoration
+            || self.solved_count != updated.solved_count;
+        *self = updated;
+        changed
+    }
 }
 
-impl RatingAlarms {
+impl Subscriptions {
     pub fn setup(store: KvStore) -> Self {
         Self { store }
     }
 
-    pub async fn all_subscribers(&self) -> Result<impl Iterator<Item = i64>, KvError> {
+    /// Subscriptions are listed in the order of their keys.
+    pub fn key(subscriber: i64, target: &str) -> String {
+        format!("{}:{}", subscriber, target.to_lowercase())
+    }
+
+    /// Key of the subscription the last poll ran out of requests at.
+    pub async fn resume_point(&self) -> Result<Option<String>, KvError> {
         self.store
-            .list()
+            .get("resume")
+            .await
+            .map(|value| value.map(KvValue::as_string))
+    }
+
+    pub async fn set_resume_point(&self, key: Option<&str>) -> Result<(), KvError> {
+        match key {
+            Some(key) => self.store.put("resume", key)?.execute().await,
+            None => self.store.delete("resume").await,
+        }
+    }
+
+    async fn keys(&self, prefix: Option<String>) -> Result<Vec<String>, KvError> {
+        let mut keys = vec![];
+        let mut cursor = None;
+        loop {
+            let mut list = self.store.list();
+            if let Some(prefix) = &prefix {
+                list = list.prefix(prefix.clone());
+            }
+            if let Some(cursor) = cursor {
+                list = list.cursor(cursor);
+            }
+            let res = list.execute().await?;
+            keys.extend(res.keys.into_iter().map(|key| key.name));
+            if res.list_complete {
+                break;
+            }
+            cursor = res.cursor;
+        }
+        Ok(keys)
+    }
+
+    /// Moves a subscription from the old layout, where the chat id alone was the key.
+    async fn migrate(&self, subscriber: i64) -> worker::Result<Option<Subscription>> {
+        let legacy = subscriber.to_string();
+        let subscription = match self.get(&legacy).await? {
+            Some(subscription) => subscription,
+            None => return Ok(None),
+        };
+        let json = serde_json::to_string(&subscription)?;
+        self.store
+            .put(&Self::key(subscriber, &subscription.target), json)?
             .execute()
+            .await?;
+        self.store.delete(&legacy).await?;
+        Ok(Some(subscription))
+    }
+
+    pub async fn all_subscriptions(&self) -> worker::Result<Vec<(i64, Subscription)>> {
+        let mut subscriptions = vec![];
+        for key in self.keys(None).await? {
+            let (subscriber, legacy) = match key.split_once(':') {
+                Some((subscriber, _)) => (subscriber, false),
+                None => (key.as_str(), true),
+            };
+            let subscriber = match subscriber.parse() {
+                Ok(subscriber) => subscriber,
+                Err(_) => continue,
+            };
+            let subscription = if legacy {
+                self.migrate(subscriber).await?
+            } else {
+                self.get(&key).await?
+            };
+            if let Some(subscription) = subscription {
+                subscriptions.push((subscriber, subscription));
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    pub async fn subscriptions(&self, subscriber: i64) -> worker::Result<Vec<Subscription>> {
+        self.migrate(subscriber).await?;
+        let mut subscriptions = vec![];
+        for key in self.keys(Some(format!("{}:", subscriber))).await? {
+            if let Some(subscription) = self.get(&key).await? {
+                subscriptions.push(subscription);
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Subscription>, KvError> {
+        self.store
+            .get(key)
             .await
-            .map(|res| res.keys.into_iter().filter_map(|key| key.name.parse().ok()))
+            .map(|option| option.and_then(|value| value.as_json().ok()))
     }
 
     pub async fn get_subscription(
         &self,
         subscriber: i64,
-    ) -> Result<Option<RatingSubscription>, KvError> {
-        self.store
-            .get(&subscriber.to_string())
+        target: &str,
+    ) -> worker::Result<Option<Subscription>> {
+        self.migrate(subscriber).await?;
+        self.get(&Self::key(subscriber, target))
             .await
-            .map(|option| option.and_then(|value| value.as_json().ok()))
+            .map_err(Into::into)
     }
 
+    /// Saves the subscription, or removes it once it watches nothing.
     pub async fn set_subscription(
         &self,
         subscriber: i64,
-        target: impl Into<String>,
-        rating: u64,
+        subscription: &Subscription,
     ) -> worker::Result<()> {
-        let json = serde_json::to_string(&RatingSubscription {
-            target: target.into(),
-            rating,
+        let key = Self::key(subscriber, &subscription.target);
+        if subscription.watch.is_empty() {
+            return self.store.delete(&key).await.map_err(Into::into);
+        }
+        let json = serde_json::to_string(subscription)?;
+        self.store
+            .put(&key, json)?
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Ratings observed by the rating poll, keyed by the lowercased handle.
+pub struct RatingHistory {
+    store: KvStore,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct RatingPoint {
+    /// Unix timestamp in milliseconds.
+    pub at: u64,
+    pub rating: u64,
+}
+
+impl RatingHistory {
+    /// Old points are dropped beyond this, which is years of daily changes.
+    const MAX_POINTS: usize = 2000;
+
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    pub async fn get(&self, handle: &str) -> Result<Vec<RatingPoint>, KvError> {
+        self.store.get(&handle.to_lowercase()).await.map(|option| {
+            option
+                .and_then(|value| value.as_json().ok())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Appends the rating unless it is already the latest one.
+    pub async fn record(&self, handle: &str, at: u64, rating: u64) -> worker::Result<()> {
+        let mut history = self.get(handle).await?;
+        if history.last().map(|point| point.rating) == Some(rating) {
+            return Ok(());
+        }
+        history.push(RatingPoint { at, rating });
+        if history.len() > Self::MAX_POINTS {
+            history.drain(..history.len() - Self::MAX_POINTS);
+        }
+        let json = serde_json::to_string(&history)?;
+        self.store
+            .put(&handle.to_lowercase(), json)?
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Handles linked to Telegram users with `/link`, keyed by the Telegram user id.
+pub struct LinkedHandles {
+    store: KvStore,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LinkedHandle {
+    pub handle: String,
+    /// Whether the user proved the ownership of the handle with `/verify`.
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PendingVerification {
+    pub handle: String,
+    pub token: String,
+}
+
+impl LinkedHandles {
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    pub async fn get(&self, user_id: i64) -> Result<Option<LinkedHandle>, KvError> {
+        self.store
+            .get(&user_id.to_string())
+            .await
+            .map(|option| option.and_then(|value| value.as_json().ok()))
+    }
+
+    pub async fn link(&self, user_id: i64, handle: &str, verified: bool) -> worker::Result<()> {
+        let json = serde_json::to_string(&LinkedHandle {
+            handle: handle.to_string(),
+            verified,
         })?;
         self.store
-            .put(&subscriber.to_string(), json)?
+            .put(&user_id.to_string(), json)?
             .execute()
             .await
             .map_err(Into::into)
     }
 
-    pub async fn unsubscribe(&self, subscriber: i64) -> Result<(), KvError> {
-        self.store.delete(&subscriber.to_string()).await
+    pub async fn unlink(&self, user_id: i64) -> Result<(), KvError> {
+        self.store.delete(&user_id.to_string()).await
+    }
+
+    pub async fn get_pending(&self, user_id: i64) -> Result<Option<PendingVerification>, KvError> {
+        self.store
+            .get(&format!("verify:{}", user_id))
+            .await
+            .map(|option| option.and_then(|value| value.as_json().ok()))
+    }
+
+    /// Keeps the verification token for an hour.
+    pub async fn set_pending(
+        &self,
+        user_id: i64,
+        pending: &PendingVerification,
+    ) -> worker::Result<()> {
+        let json = serde_json::to_string(pending)?;
+        self.store
+            .put(&format!("verify:{}", user_id), json)?
+            .expiration_ttl(60 * 60)
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn clear_pending(&self, user_id: i64) -> Result<(), KvError> {
+        self.store.delete(&format!("verify:{}", user_id)).await
     }
 }
 
+/// Telegram file ids of rendered profile cards, keyed by `card::cache_key`.
 pub struct ProfileImages {
     store: KvStore,
 }
@@ -65,18 +448,366 @@ impl ProfileImages {
         Self { store }
     }
 
-    pub async fn get_id(&self, handle: &str) -> Result<Option<String>, KvError> {
+    pub async fn get_id(&self, key: &str) -> Result<Option<String>, KvError> {
         self.store
-            .get(handle)
+            .get(key)
             .await
             .map(|value| value.map(KvValue::as_string))
     }
 
-    pub async fn set_id(&self, handle: &str, file_id: &str) -> Result<(), KvError> {
+    pub async fn set_id(&self, key: &str, file_id: &str) -> Result<(), KvError> {
+        self.store
+            .put(key, file_id)?
+            .expiration_ttl(60 * 60 * 24)
+            .execute()
+            .await
+    }
+}
+
+pub struct ChatSettings {
+    store: KvStore,
+}
+
+impl ChatSettings {
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    pub async fn get_language(&self, chat_id: i64) -> Result<Option<Language>, KvError> {
+        self.store
+            .get(&format!("language:{}", chat_id))
+            .await
+            .map(|value| value.and_then(|value| Language::from_code(&value.as_string())))
+    }
+
+    pub async fn set_language(&self, chat_id: i64, language: Language) -> Result<(), KvError> {
+        self.store
+            .put(&format!("language:{}", chat_id), language.code())?
+            .execute()
+            .await
+    }
+
+    /// Whether only members with a verified linked handle may join the leaderboard.
+    pub async fn get_verified_only(&self, chat_id: i64) -> Result<bool, KvError> {
+        self.store
+            .get(&format!("verified_only:{}", chat_id))
+            .await
+            .map(|value| value.is_some())
+    }
+
+    pub async fn set_verified_only(
+        &self,
+        chat_id: i64,
+        verified_only: bool,
+    ) -> Result<(), KvError> {
+        let key = format!("verified_only:{}", chat_id);
+        if verified_only {
+            self.store.put(&key, "1")?.execute().await
+        } else {
+            self.store.delete(&key).await
+        }
+    }
+}
+
+/// Leaderboard members of each chat, keyed by the chat id.
+pub struct ChatMembers {
+    store: KvStore,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Member {
+    pub user_id: i64,
+    pub handle: String,
+}
+
+pub enum Join {
+    Joined,
+    /// The chat already has as many members as allowed.
+    Full,
+    /// Someone else in the chat joined with the handle.
+    Taken,
+}
+
+impl ChatMembers {
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    pub async fn get(&self, chat_id: i64) -> Result<Vec<Member>, KvError> {
+        self.store.get(&chat_id.to_string()).await.map(|option| {
+            option
+                .and_then(|value| value.as_json().ok())
+                .unwrap_or_default()
+        })
+    }
+
+    async fn set(&self, chat_id: i64, members: &[Member]) -> worker::Result<()> {
+        if members.is_empty() {
+            return self
+                .store
+                .delete(&chat_id.to_string())
+                .await
+                .map_err(Into::into);
+        }
+        let json = serde_json::to_string(members)?;
+        self.store
+            .put(&chat_id.to_string(), json)?
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Registers the handle for the user, replacing the one they joined with before.
+    pub async fn join(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        handle: &str,
+        limit: usize,
+    ) -> worker::Result<Join> {
+        let mut members = self.get(chat_id).await?;
+        members.retain(|member| member.user_id != user_id);
+        if members
+            .iter()
+            .any(|member| member.handle.eq_ignore_ascii_case(handle))
+        {
+            return Ok(Join::Taken);
+        }
+        if members.len() >= limit {
+            return Ok(Join::Full);
+        }
+        members.push(Member {
+            user_id,
+            handle: handle.to_string(),
+        });
+        self.set(chat_id, &members).await?;
+        Ok(Join::Joined)
+    }
+
+    /// Removes the members matching the predicate, returning the handles removed.
+    pub async fn remove(
+        &self,
+        chat_id: i64,
+        predicate: impl Fn(&Member) -> bool,
+    ) -> worker::Result<Vec<String>> {
+        let (removed, members): (Vec<_>, Vec<_>) = self
+            .get(chat_id)
+            .await?
+            .into_iter()
+            .partition(|member| predicate(member));
+        if !removed.is_empty() {
+            self.set(chat_id, &members).await?;
+        }
+        Ok(removed.into_iter().map(|member| member.handle).collect())
+    }
+}
+
+/// Daily problem settings of each chat under `config:{chat}`, the chats due at each time of the
+/// day under `time:{HHMM}`, so a cron tick reads a single key, and the problems posted to each
+/// chat under `history:{chat}`.
+pub struct DailyProblems {
+    store: KvStore,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyConfig {
+    pub chat_id: i64,
+    pub query: String,
+    /// Minutes after midnight in KST.
+    pub minute: u64,
+    pub pin: bool,
+    /// Days since the Unix epoch in KST of the last post.
+    pub last_day: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyPost {
+    pub problem_id: u32,
+    /// Unix timestamp in milliseconds.
+    pub at: u64,
+}
+
+impl DailyProblems {
+    /// Problems are not posted again to the same chat within this many days.
+    const HISTORY_DAYS: u64 = 90;
+
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    fn config_key(chat_id: i64) -> String {
+        format!("config:{}", chat_id)
+    }
+
+    fn time_key(minute: u64) -> String {
+        format!("time:{:02}{:02}", minute / 60, minute % 60)
+    }
+
+    /// Chats whose problem is due at the minute after midnight in KST.
+    pub async fn chats_at(&self, minute: u64) -> Result<Vec<i64>, KvError> {
+        self.store.get(&Self::time_key(minute)).await.map(|option| {
+            option
+                .and_then(|value| value.as_json().ok())
+                .unwrap_or_default()
+        })
+    }
+
+    async fn set_chats_at(&self, minute: u64, chats: &[i64]) -> worker::Result<()> {
+        let key = Self::time_key(minute);
+        if chats.is_empty() {
+            return self.store.delete(&key).await.map_err(Into::into);
+        }
+        let json = serde_json::to_string(chats)?;
         self.store
-            .put(handle, file_id)?
-            .expiration_ttl(60 * 60 * 24 * 7)
+            .put(&key, json)?
             .execute()
             .await
+            .map_err(Into::into)
+    }
+
+    pub async fn config(&self, chat_id: i64) -> Result<Option<DailyConfig>, KvError> {
+        self.store
+            .get(&Self::config_key(chat_id))
+            .await
+            .map(|option| option.and_then(|value| value.as_json().ok()))
+    }
+
+    /// Replaces the setting of the chat, moving it to its new time.
+    pub async fn set_config(&self, config: &DailyConfig) -> worker::Result<()> {
+        if let Some(old) = self.config(config.chat_id).await? {
+            if old.minute != config.minute {
+                let mut chats = self.chats_at(old.minute).await?;
+                chats.retain(|&chat_id| chat_id != config.chat_id);
+                self.set_chats_at(old.minute, &chats).await?;
+            }
+        }
+        let mut chats = self.chats_at(config.minute).await?;
+        if !chats.contains(&config.chat_id) {
+            chats.push(config.chat_id);
+            self.set_chats_at(config.minute, &chats).await?;
+        }
+        self.update_config(config).await
+    }
+
+    /// Saves the setting of the chat as is, for changes that keep its time.
+    pub async fn update_config(&self, config: &DailyConfig) -> worker::Result<()> {
+        let json = serde_json::to_string(config)?;
+        self.store
+            .put(&Self::config_key(config.chat_id), json)?
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns whether the chat had daily problems.
+    pub async fn remove_config(&self, chat_id: i64) -> worker::Result<bool> {
+        let config = match self.config(chat_id).await? {
+            Some(config) => config,
+            None => return Ok(false),
+        };
+        let mut chats = self.chats_at(config.minute).await?;
+        chats.retain(|&other| other != chat_id);
+        self.set_chats_at(config.minute, &chats).await?;
+        self.store.delete(&Self::config_key(chat_id)).await?;
+        Ok(true)
+    }
+
+    pub async fn history(&self, chat_id: i64) -> Result<Vec<DailyPost>, KvError> {
+        self.store
+            .get(&format!("history:{}", chat_id))
+            .await
+            .map(|option| {
+                option
+                    .and_then(|value| value.as_json().ok())
+                    .unwrap_or_default()
+            })
+    }
+
+    /// Adds the problem to the history, dropping posts older than the repeat window.
+    pub async fn record(&self, chat_id: i64, problem_id: u32, at: u64) -> worker::Result<()> {
+        let window = Self::HISTORY_DAYS * 24 * 60 * 60 * 1000;
+        let mut history = self.history(chat_id).await?;
+        history.retain(|post| post.at + window > at);
+        history.push(DailyPost { problem_id, at });
+        let json = serde_json::to_string(&history)?;
+        self.store
+            .put(&format!("history:{}", chat_id), json)?
+            .expiration_ttl(Self::HISTORY_DAYS * 24 * 60 * 60)
+            .execute()
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Queries of the reroll buttons under short ids, as Telegram limits callback data to 64 bytes.
+pub struct RandomQueries {
+    store: KvStore,
+}
+
+impl RandomQueries {
+    /// Buttons stop working this many seconds after their query was last used.
+    const TTL: u64 = 60 * 60 * 24 * 30;
+
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    /// Stores the query, or extends its expiration, and returns its id.
+    pub async fn save(&self, query: &str) -> Result<String, KvError> {
+        let id = format!("{:016x}", fnv1a(query.as_bytes()));
+        self.store
+            .put(&id, query)?
+            .expiration_ttl(Self::TTL)
+            .execute()
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<String>, KvError> {
+        self.store
+            .get(id)
+            .await
+            .map(|value| value.map(KvValue::as_string))
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` gives the same ids across Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub struct ResponseCache {
+    store: KvStore,
+}
+
+impl ResponseCache {
+    pub fn setup(store: KvStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait(?Send)]
+impl Cache for ResponseCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.store
+            .get(key)
+            .await
+            .ok()
+            .flatten()
+            .map(KvValue::as_string)
+    }
+
+    async fn put(&self, key: &str, value: String, ttl: u64) {
+        // KV rejects keys longer than 512 bytes, and long search queries are not worth caching
+        if key.len() > 512 {
+            return;
+        }
+        if let Ok(put) = self.store.put(key, value) {
+            if let Err(e) = put.expiration_ttl(ttl).execute().await {
+                web_sys::console::error_1(&format!("Cache Error: {:?}", e).into());
+            }
+        }
     }
 }
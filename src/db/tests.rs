@@ -0,0 +1,168 @@
+use crate::solved::{ClassDecoration, SearchPage};
+
+use super::*;
+
+const USER_SHOW: &str = include_str!("../../tests/fixtures/user_show.json");
+const SEARCH_PROBLEM: &str = include_str!("../../tests/fixtures/search_problem.json");
+
+fn user() -> User {
+    serde_json::from_str(USER_SHOW).unwrap()
+}
+
+fn problems() -> Vec<Problem> {
+    let page: SearchPage<Problem> = serde_json::from_str(SEARCH_PROBLEM).unwrap();
+    page.items
+}
+
+fn watching(watch: Watch) -> Subscription {
+    let mut subscription = Subscription::new(&user());
+    subscription.watch = watch;
+    subscription
+}
+
+fn watch_all() -> Watch {
+    Watch {
+        ratings: true,
+        tier: true,
+        class: true,
+        solved_step: Some(100),
+        problems: true,
+    }
+}
+
+#[test]
+fn unchanged_user_has_no_changes() {
+    let subscription = watching(watch_all());
+    assert!(subscription.changes(&user(), None).is_empty());
+}
+
+#[test]
+fn reports_rating_changes_both_ways() {
+    let subscription = watching(Watch::ratings());
+    let mut user = user();
+    user.rating += 12;
+    let changes = subscription.changes(&user, None);
+    assert!(matches!(changes[..], [Change::Rating { prev: 2513 }]));
+    user.rating = 2400;
+    let changes = subscription.changes(&user, None);
+    assert!(matches!(changes[..], [Change::Rating { prev: 2513 }]));
+}
+
+#[test]
+fn reports_arena_ratings_only_once_seen() {
+    let mut subscription = watching(Watch::ratings());
+    let mut user = user();
+    user.arena_rating = 1200;
+    let changes = subscription.changes(&user, None);
+    assert!(matches!(changes[..], [Change::ArenaRating { prev: 0 }]));
+    subscription.arena_rating = None;
+    assert!(subscription.changes(&user, None).is_empty());
+}
+
+#[test]
+fn ignores_what_is_not_watched() {
+    let subscription = watching(Watch::default());
+    let mut user = user();
+    user.rating += 100;
+    user.tier += 1;
+    user.class += 1;
+    user.solved_count += 100;
+    assert!(subscription.changes(&user, None).is_empty());
+}
+
+#[test]
+fn reports_tier_changes() {
+    let subscription = watching(Watch {
+        tier: true,
+        ..Default::default()
+    });
+    let mut user = user();
+    user.tier -= 1;
+    let changes = subscription.changes(&user, None);
+    assert!(matches!(changes[..], [Change::Tier { prev: 24 }]));
+}
+
+#[test]
+fn reports_class_promotions_only() {
+    let subscription = watching(Watch {
+        class: true,
+        ..Default::default()
+    });
+    let mut user = user();
+    user.class += 1;
+    user.class_decoration = ClassDecoration::None;
+    assert!(matches!(
+        subscription.changes(&user, None)[..],
+        [Change::Class]
+    ));
+    user.class -= 2;
+    assert!(subscription.changes(&user, None).is_empty());
+}
+
+#[test]
+fn reports_solved_steps() {
+    let subscription = watching(Watch {
+        solved_step: Some(100),
+        ..Default::default()
+    });
+    let mut user = user();
+    user.solved_count = 1599;
+    assert!(subscription.changes(&user, None).is_empty());
+    user.solved_count = 1600;
+    let changes = subscription.changes(&user, None);
+    assert!(matches!(changes[..], [Change::Solved(1600)]));
+    // Passing several steps at once notifies once, for the last one
+    user.solved_count = 1750;
+    let changes = subscription.changes(&user, None);
+    assert!(matches!(changes[..], [Change::Solved(1700)]));
+}
+
+#[test]
+fn reports_problems_entering_the_top_100() {
+    let mut subscription = watching(Watch {
+        problems: true,
+        ..Default::default()
+    });
+    subscription.top_100 = Some(vec![1000]);
+    let top_100 = problems();
+    let mut user = user();
+    user.solved_count += 3;
+    let changes = subscription.changes(&user, Some(&top_100));
+    match &changes[..] {
+        [Change::NewSolves { solves, more }] => {
+            let ids = solves
+                .iter()
+                .map(|problem| problem.problem_id)
+                .collect::<Vec<_>>();
+            assert_eq!(ids, [1001]);
+            assert_eq!(*more, 2);
+        }
+        _ => panic!("expected new solves only"),
+    }
+}
+
+#[test]
+fn first_poll_of_new_solves_reports_nothing() {
+    let subscription = watching(Watch {
+        problems: true,
+        ..Default::default()
+    });
+    assert!(subscription.top_100.is_none());
+    let top_100 = problems();
+    let mut user = user();
+    user.solved_count += 3;
+    assert!(subscription.changes(&user, Some(&top_100)).is_empty());
+}
+
+#[test]
+fn update_records_the_current_values() {
+    let mut subscription = watching(watch_all());
+    assert!(!subscription.update(&user()));
+    let mut user = user();
+    user.rating += 1;
+    user.class_decoration = ClassDecoration::Silver;
+    assert!(subscription.update(&user));
+    assert_eq!(subscription.rating, user.rating);
+    assert_eq!(subscription.class_decoration, Some(1));
+    assert!(subscription.changes(&user, None).is_empty());
+}
@@ -1,27 +1,52 @@
-use image::jpeg::JpegEncoder;
-use serde_json::{Map, Value};
+use std::cmp::Reverse;
+use std::collections::HashSet;
 use telbot_cf_worker::types::file::{InputFile, InputFileVariant};
 use telbot_cf_worker::types::markup::{
     InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup, ParseMode,
 };
-use telbot_cf_worker::types::message::{SendDocument, SendMessage};
+use telbot_cf_worker::types::message::{EditMessageText, SendMessage, SendPhoto};
 use telbot_cf_worker::types::query::{
     InlineQueryResult, InlineQueryResultKind, InputMessageContent,
 };
 use worker::js_sys::{JsString, RegExp};
 use worker::{CfProperties, Fetch, Method, Request, RequestInit};
 
+use crate::db::{DailyConfig, Subscription};
+use crate::i18n::{fill, Language};
+use crate::solved::{
+    ClassDecoration, ClassStat, Organization, Problem, ProblemStat, SolvedError, Tag, User,
+    UserRanking,
+};
+use crate::{card, chart, Leaderboard};
+
 pub fn escape_markdown_v2(s: &str) -> String {
     let regex = RegExp::new(r"[_*\[\]()~`>#+-=|\{\}\.!]", "g");
     JsString::from(s).replace_by_pattern(&regex, r"\$&").into()
 }
 
+const TIER_GROUPS: [&str; 6] = [
+    "🤎 Bronze",
+    "🤍 Silver",
+    "💛 Gold",
+    "💚 Platinum",
+    "💙 Diamond",
+    "❤️ Ruby",
+];
+
+pub fn level_to_group(level: u64) -> Option<&'static str> {
+    match level {
+        0 => Some("🖤 Unrated"),
+        1..=30 => Some(TIER_GROUPS[(level as usize - 1) / 5]),
+        _ => None,
+    }
+}
+
 pub fn level_to_name(level: u64) -> Option<String> {
     match level {
         0 => Some("🖤 Unrated".to_string()),
         1..=30 => Some(format!(
             "{} {}",
-            ["🤎 Bronze", "🤍 Silver", "💛 Gold", "💚 Platinum", "💙 Diamond", "❤️ Ruby"][(level as usize - 1) / 5],
+            TIER_GROUPS[(level as usize - 1) / 5],
             ["V", "IV", "III", "II", "I"][(level as usize - 1) % 5]
         )),
         _ => None,
@@ -33,7 +58,7 @@ pub fn tier_to_name(level: u64) -> Option<String> {
         0 => Some("🖤 Unranked".to_string()),
         1..=30 => Some(format!(
             "{} {}",
-            ["🤎 Bronze", "🤍 Silver", "💛 Gold", "💚 Platinum", "💙 Diamond", "❤️ Ruby"][(level as usize - 1) / 5],
+            TIER_GROUPS[(level as usize - 1) / 5],
             ["V", "IV", "III", "II", "I"][(level as usize - 1) % 5]
         )),
         31 => Some("💜 Master".to_string()),
@@ -41,51 +66,52 @@ pub fn tier_to_name(level: u64) -> Option<String> {
     }
 }
 
-pub fn class_to_name(class: u64, decoration: &str) -> Option<String> {
+pub fn arena_tier_to_name(tier: u64) -> Option<&'static str> {
+    const ARENA_TIERS: [&str; 14] = [
+        "Unrated", "C", "C+", "B", "B+", "A", "A+", "S", "S+", "SS", "SS+", "SSS", "SSS+", "X",
+    ];
+    ARENA_TIERS.get(tier as usize).copied()
+}
+
+pub fn class_to_name(class: u64, decoration: ClassDecoration) -> String {
     let decoration = match decoration {
-        "none" => Some(""),
-        "silver" => Some(r"\+"),
-        "gold" => Some(r"\+\+"),
-        _ => None,
+        ClassDecoration::None => "",
+        ClassDecoration::Silver => r"\+",
+        ClassDecoration::Gold => r"\+\+",
     };
-    decoration.map(|decoration| format!("{}{}", class, decoration))
+    format!("{}{}", class, decoration)
 }
 
-pub fn search_problem_to_query(result: &[Map<String, Value>]) -> Vec<InlineQueryResult> {
+/// Uses the English title for English chats if solved.ac has one.
+pub fn problem_title(problem: &Problem, language: Language) -> &str {
+    problem
+        .titles
+        .iter()
+        .find(|title| title.language == language.code())
+        .map_or(&problem.title_ko, |title| &title.title)
+}
+
+pub fn search_problem_to_query(result: &[Problem], language: Language) -> Vec<InlineQueryResult> {
+    let t = language.texts();
     result
         .iter()
-        .map(|obj| {
-            let id = extract_u64_or_na(obj, "problemId");
-            let title = extract_str_or_na(obj, "titleKo");
-            let level = obj
-                .get("level")
-                .and_then(Value::as_u64)
-                .and_then(level_to_name)
-                .unwrap_or_else(|| "N/A".to_string());
-            let partial = obj
-                .get("isPartial")
-                .and_then(Value::as_bool)
-                .map_or("", |is_partial| {
-                    if is_partial {
-                        " [부분 점수 / 서브태스크]"
-                    } else {
-                        ""
-                    }
-                });
-            let solvable =
-                obj.get("isSolvable")
-                    .and_then(Value::as_bool)
-                    .map_or("", |is_solvable| {
-                        if is_solvable {
-                            ""
-                        } else {
-                            " \\(채점 준비중\\)"
-                        }
-                    });
+        .map(|problem| {
+            let level = level_to_name(problem.level).unwrap_or_else(|| "N/A".to_string());
+            let partial = if problem.is_partial {
+                format!(" \\[{}\\]", escape_markdown_v2(t.partial))
+            } else {
+                "".to_string()
+            };
+            let solvable = if problem.is_solvable {
+                "".to_string()
+            } else {
+                format!(" \\({}\\)", escape_markdown_v2(t.not_solvable))
+            };
+            let title = problem_title(problem, language);
             let content = format!(
                 "[{} \\- \\#{} {}](https://boj.kr/{1})\n{}{}",
                 level,
-                id,
+                problem.problem_id,
                 escape_markdown_v2(title),
                 partial,
                 solvable
@@ -106,190 +132,1189 @@ pub fn search_problem_to_query(result: &[Map<String, Value>]) -> Vec<InlineQuery
                 thumb_width: None,
                 thumb_height: None,
             }
-            .with_id(format!("SPTQ{}", id))
+            .with_id(format!("SPTQ{}", problem.problem_id))
         })
         .collect()
 }
 
-pub fn problem_show_to_message(chat_id: i64, result: &[Map<String, Value>]) -> SendMessage {
-    let mut text: String = result
+fn problem_to_line(problem: &Problem, language: Language) -> String {
+    let level = level_to_name(problem.level).unwrap_or_else(|| "N/A".to_string());
+    format!(
+        "[{} \\- \\#{} {}](https://boj.kr/{1})",
+        level,
+        problem.problem_id,
+        escape_markdown_v2(problem_title(problem, language)),
+    )
+}
+
+pub fn problem_show_to_message(
+    chat_id: i64,
+    result: &[Problem],
+    language: Language,
+) -> SendMessage {
+    let text = result
         .iter()
-        .map(|problem| {
-            let id = extract_u64_or_na(problem, "problemId");
-            let title = extract_str_or_na(problem, "titleKo");
-            let level = problem
-                .get("level")
-                .and_then(Value::as_u64)
-                .and_then(level_to_name)
-                .unwrap_or_else(|| "N/A".to_string());
+        .map(|problem| problem_to_line(problem, language))
+        .collect::<Vec<_>>()
+        .join("\n");
+    SendMessage::new(chat_id, text)
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn tag_to_name<'a>(tag: &'a Tag, language: &str) -> &'a str {
+    tag.display_names
+        .iter()
+        .find(|name| name.language == language)
+        .or_else(|| tag.display_names.first())
+        .map_or(&tag.key, |name| &name.name)
+}
+
+fn tag_to_text(tag: &Tag, language: Language) -> String {
+    let t = language.texts();
+    let names = tag
+        .display_names
+        .iter()
+        .map(|name| {
+            if name.short.is_empty() || name.short == name.name {
+                escape_markdown_v2(&name.name)
+            } else {
+                format!(
+                    "{} \\({}\\)",
+                    escape_markdown_v2(&name.name),
+                    escape_markdown_v2(&name.short)
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" / ");
+    let aliases = if tag.aliases.is_empty() {
+        "".to_string()
+    } else {
+        let aliases = tag
+            .aliases
+            .iter()
+            .map(|alias| format!("`{}`", escape_markdown_v2(&alias.alias)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("\n{}", fill(t.aliases, &[&aliases]))
+    };
+    format!(
+        "*{}* `\\#{}`\n{}{}\n{}",
+        escape_markdown_v2(tag_to_name(tag, language.code())),
+        escape_markdown_v2(&tag.key),
+        names,
+        aliases,
+        fill(t.problem_count, &[&tag.problem_count])
+    )
+}
+
+pub fn tag_to_message(chat_id: i64, tag: &Tag, others: &[Tag], language: Language) -> SendMessage {
+    let t = language.texts();
+    let mut text = tag_to_text(tag, language);
+    if !others.is_empty() {
+        let others = others
+            .iter()
+            .map(|tag| format!("`\\#{}`", escape_markdown_v2(&tag.key)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        text.push_str(&format!("\n\n{}", fill(t.other_tags, &[&others])));
+    }
+
+    let callback_data = format!("tag:{}", tag.key);
+    let mut result = SendMessage::new(chat_id, text).with_parse_mode(ParseMode::MarkdownV2);
+    if callback_data.len() <= 64 {
+        result = result.with_reply_markup(InlineKeyboardMarkup {
+            inline_keyboard: vec![vec![InlineKeyboardButton {
+                text: t.tag_problems_button.to_string(),
+                kind: InlineKeyboardButtonKind::Callback { callback_data },
+            }]],
+        });
+    }
+    result
+}
+
+pub fn tag_list_to_message(chat_id: i64, tags: &[Tag], language: Language) -> SendMessage {
+    let t = language.texts();
+    let mut tags = tags.iter().filter(|tag| !tag.is_meta).collect::<Vec<_>>();
+    tags.sort_by_key(|tag| Reverse(tag.problem_count));
+    let list = tags
+        .iter()
+        .take(30)
+        .map(|tag| {
             format!(
-                "[{} \\- \\#{} {}](https://boj.kr/{1})\n",
-                level,
-                id,
-                escape_markdown_v2(title),
+                "`\\#{}` {} \\({}\\)",
+                escape_markdown_v2(&tag.key),
+                escape_markdown_v2(tag_to_name(tag, language.code())),
+                fill(t.problem_count_plain, &[&tag.problem_count])
             )
         })
-        .collect();
-    text.pop();
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = format!("{}\n\n{}", t.usage_tag, list);
+    SendMessage::new(chat_id, text).with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn search_tag_to_query(result: &[Tag], language: Language) -> Vec<InlineQueryResult> {
+    let t = language.texts();
+    result
+        .iter()
+        .map(|tag| {
+            InlineQueryResultKind::Article {
+                title: tag_to_name(tag, language.code()).to_string(),
+                description: Some(format!(
+                    "#{} · {}",
+                    tag.key,
+                    fill(t.problem_count_plain, &[&tag.problem_count])
+                )),
+                input_message_content: InputMessageContent::Text {
+                    message_text: tag_to_text(tag, language),
+                    disable_web_page_preview: Some(true),
+                    entities: None,
+                    parse_mode: Some(ParseMode::MarkdownV2),
+                },
+                url: None,
+                hide_url: None,
+                thumb_url: None,
+                thumb_width: None,
+                thumb_height: None,
+            }
+            .with_id(format!("STTQ{}", tag.key))
+        })
+        .collect()
+}
+
+fn problem_detail_to_text(problem: &Problem, language: Language) -> String {
+    let t = language.texts();
+    let mut flags = vec![];
+    if problem.sprout {
+        flags.push(t.sprout);
+    }
+    if problem.is_level_locked {
+        flags.push(t.level_locked);
+    }
+    if problem.is_partial {
+        flags.push(t.partial);
+    }
+    if !problem.is_solvable {
+        flags.push(t.not_solvable);
+    }
+    let flags = if flags.is_empty() {
+        "".to_string()
+    } else {
+        format!("\n{}", escape_markdown_v2(&flags.join(" · ")))
+    };
+    let tags = if problem.tags.is_empty() {
+        "".to_string()
+    } else {
+        let names = problem
+            .tags
+            .iter()
+            .map(|tag| {
+                format!(
+                    "`{}`",
+                    escape_markdown_v2(tag_to_name(tag, language.code()))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("\n{}", fill(t.tags, &[&names]))
+    };
+
+    format!(
+        "{line}{flags}\n{summary}{tags}",
+        line = problem_to_line(problem, language),
+        flags = flags,
+        summary = fill(
+            t.problem_summary,
+            &[
+                &problem.accepted_user_count,
+                &escape_markdown_v2(&format!("{:.2}", problem.average_tries)),
+            ]
+        ),
+        tags = tags,
+    )
+}
+
+fn problem_detail_keyboard(problem: &Problem, language: Language) -> InlineKeyboardMarkup {
+    let t = language.texts();
+    let mut inline_keyboard = vec![vec![InlineKeyboardButton {
+        text: t.view_on_boj.to_string(),
+        kind: InlineKeyboardButtonKind::Url {
+            url: format!("https://www.acmicpc.net/problem/{}", problem.problem_id),
+        },
+    }]];
+    inline_keyboard.extend(problem.tags.chunks(2).map(|tags| {
+        tags.iter()
+            .map(|tag| InlineKeyboardButton {
+                text: format!("#{}", tag_to_name(tag, language.code())),
+                kind: InlineKeyboardButtonKind::Url {
+                    url: format!("https://solved.ac/search?query=%23{}", tag.key),
+                },
+            })
+            .collect()
+    }));
+    InlineKeyboardMarkup { inline_keyboard }
+}
+
+pub fn problem_detail_to_message(
+    chat_id: i64,
+    problem: &Problem,
+    language: Language,
+) -> SendMessage {
+    SendMessage::new(chat_id, problem_detail_to_text(problem, language))
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+        .with_reply_markup(problem_detail_keyboard(problem, language))
+}
+
+pub fn daily_problem_to_message(
+    chat_id: i64,
+    problem: &Problem,
+    language: Language,
+) -> SendMessage {
+    let text = format!(
+        "*{}*\n\n{}",
+        language.texts().daily_header,
+        problem_detail_to_text(problem, language)
+    );
     SendMessage::new(chat_id, text)
         .disable_web_page_preview()
         .with_parse_mode(ParseMode::MarkdownV2)
+        .with_reply_markup(problem_detail_keyboard(problem, language))
 }
 
-pub async fn user_show_to_message(
+pub fn daily_config_to_text(config: &DailyConfig, language: Language) -> String {
+    let t = language.texts();
+    let time = format!("{:02}:{:02}", config.minute / 60, config.minute % 60);
+    let text = fill(t.daily_on, &[&time, &config.query]);
+    if config.pin {
+        format!("{}\n{}", text, t.daily_pinned)
+    } else {
+        text
+    }
+}
+
+fn random_problem_keyboard(query_id: &str, language: Language) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup {
+        inline_keyboard: vec![vec![InlineKeyboardButton {
+            text: language.texts().reroll.to_string(),
+            kind: InlineKeyboardButtonKind::Callback {
+                callback_data: format!("reroll:{}", query_id),
+            },
+        }]],
+    }
+}
+
+pub fn random_problem_to_message(
     chat_id: i64,
-    result: Map<String, Value>,
-    file: Option<InputFileVariant>,
-) -> worker::Result<SendDocument> {
-    let handle = extract_str_or_na(&result, "handle");
-    let rank = extract_u64_or_na(&result, "rank");
-    let tier = result
-        .get("tier")
-        .and_then(Value::as_u64)
-        .and_then(tier_to_name)
-        .unwrap_or_else(|| "N/A".to_string());
-    let class = result.get("class").and_then(Value::as_u64);
-    let class_deco = extract_str_or_na(&result, "classDecoration");
-    let class_name = class
-        .and_then(|class| class_to_name(class, class_deco))
-        .unwrap_or_else(|| "N/A".to_string());
-    let rating = extract_u64_or_na(&result, "rating");
-    let problem_rating = extract_u64_or_na(&result, "ratingByProblemsSum");
-    let class_rating = extract_u64_or_na(&result, "ratingByClass");
-    let solve_rating = extract_u64_or_na(&result, "ratingBySolvedCount");
-    let vote_rating = extract_u64_or_na(&result, "ratingByVoteCount");
-    let bio = extract_str_or_na(&result, "bio");
-    let bio = if bio.is_empty() {
+    problem: &Problem,
+    query_id: &str,
+    language: Language,
+) -> SendMessage {
+    problem_show_to_message(chat_id, std::slice::from_ref(problem), language)
+        .with_reply_markup(random_problem_keyboard(query_id, language))
+}
+
+pub fn random_problem_to_edit(
+    chat_id: i64,
+    message_id: i64,
+    problem: &Problem,
+    query_id: &str,
+    language: Language,
+) -> EditMessageText {
+    EditMessageText::new(chat_id, message_id, problem_to_line(problem, language))
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+        .with_reply_markup(random_problem_keyboard(query_id, language))
+}
+
+fn profile_image_url(user: &User) -> String {
+    user.profile_image_url.as_deref().map_or_else(
+        || "https://static.solved.ac/misc/360x360/default_profile.png".into(),
+        |url| url.replace("profile/", "profile/360x360/"),
+    )
+}
+
+fn user_to_caption(user: &User, language: Language) -> String {
+    let t = language.texts();
+    let tier = tier_to_name(user.tier).unwrap_or_else(|| "N/A".to_string());
+    let class_name = class_to_name(user.class, user.class_decoration);
+    let bio = if user.bio.is_empty() {
         "".to_string()
     } else {
-        format!("_{}_\n\n", escape_markdown_v2(bio))
+        format!("_{}_\n\n", escape_markdown_v2(&user.bio))
     };
-    let solve_count = extract_u64_or_na(&result, "solvedCount");
-    let vote_count = extract_u64_or_na(&result, "voteCount");
-    let rival_count = extract_u64_or_na(&result, "rivalCount");
+    let arena = if user.arena_competed_round_count == 0 {
+        "".to_string()
+    } else {
+        format!(
+            "\n{} *{}* *{}*",
+            t.arena,
+            escape_markdown_v2(arena_tier_to_name(user.arena_tier).unwrap_or("N/A")),
+            user.arena_rating
+        )
+    };
+
+    format!(
+        "{bio}{tier_class}\n{summary}\n{rating}{arena}",
+        bio = bio,
+        tier_class = fill(t.user_tier_class, &[&tier, &class_name]),
+        summary = fill(
+            t.user_summary,
+            &[
+                &user.rank,
+                &user.solved_count,
+                &user.vote_count,
+                &user.rival_count
+            ]
+        ),
+        rating = fill(
+            t.user_rating,
+            &[
+                &user.rating,
+                &user.rating_by_problems_sum,
+                &user.rating_by_class,
+                &user.rating_by_solved_count,
+                &user.rating_by_vote_count
+            ]
+        ),
+        arena = arena,
+    )
+}
 
-    let (profile, thumbnail) = if let Some(file) = file {
-        (file, None)
+pub fn search_user_to_query(result: &[User], language: Language) -> Vec<InlineQueryResult> {
+    let t = language.texts();
+    result
+        .iter()
+        .map(|user| {
+            let tier = tier_to_name(user.tier).unwrap_or_else(|| "N/A".to_string());
+            InlineQueryResultKind::Article {
+                title: user.handle.clone(),
+                description: Some(fill(t.user_description, &[&tier, &user.rating])),
+                input_message_content: InputMessageContent::Text {
+                    message_text: format!(
+                        "[{}](https://solved.ac/profile/{})\n{}",
+                        escape_markdown_v2(&user.handle),
+                        user.handle,
+                        user_to_caption(user, language)
+                    ),
+                    disable_web_page_preview: Some(true),
+                    entities: None,
+                    parse_mode: Some(ParseMode::MarkdownV2),
+                },
+                url: None,
+                hide_url: None,
+                thumb_url: Some(profile_image_url(user)),
+                thumb_width: Some(360),
+                thumb_height: Some(360),
+            }
+            .with_id(format!("SUTQ{}", user.handle))
+        })
+        .collect()
+}
+
+async fn fetch_image(url: &str) -> worker::Result<Vec<u8>> {
+    Fetch::Request(Request::new_with_init(
+        url,
+        RequestInit::new()
+            .with_method(Method::Get)
+            .with_cf_properties(CfProperties {
+                cache_everything: Some(true),
+                ..Default::default()
+            }),
+    )?)
+    .send()
+    .await?
+    .bytes()
+    .await
+}
+
+pub async fn user_show_to_message(
+    chat_id: i64,
+    user: User,
+    file: Option<InputFileVariant>,
+    language: Language,
+) -> worker::Result<SendPhoto> {
+    let t = language.texts();
+    let card = if let Some(file) = file {
+        file
     } else {
-        let profile_image = result
-            .get("profileImageUrl")
-            .and_then(Value::as_str)
-            .map_or_else(
-                || "https://static.solved.ac/misc/360x360/default_profile.png".into(),
-                |url| url.replace("profile/", "profile/360x360/"),
-            );
+        // The card draws a placeholder if the avatar cannot be fetched
+        let avatar = fetch_image(&profile_image_url(&user))
+            .await
+            .ok()
+            .and_then(|image| image::load_from_memory(&image).ok());
 
-        let image = Fetch::Request(Request::new_with_init(
-            &profile_image,
-            &RequestInit::new()
-                .with_method(Method::Get)
-                .with_cf_properties(CfProperties {
-                    cache_everything: Some(true),
-                    ..Default::default()
-                }),
-        )?)
-        .send()
-        .await?
-        .bytes()
-        .await?;
-
-        let png = image::load_from_memory_with_format(&image, image::ImageFormat::Png).unwrap();
-        let buffer = image::imageops::thumbnail(&png, 256, 256);
-        let mut thumbnail = vec![];
-        JpegEncoder::new_with_quality(&mut thumbnail, 50)
-            .encode_image(&buffer)
-            .unwrap();
-
-        let profile_image = InputFile {
-            name: handle.to_string(),
-            data: vec![0],
-            mime: "application/octet-stream".to_string(),
-        };
-
-        let thumbnail_image = InputFile {
-            name: "thumbnail".to_string(),
-            data: thumbnail,
-            mime: "image/jpg".to_string(),
-        };
-        (profile_image.into(), Some(thumbnail_image.into()))
+        let card = card::render_profile_card(&user, avatar.as_ref());
+        let png = chart::encode_png(&card)
+            .map_err(|e| worker::Error::RustError(format!("Image Error: {}", e)))?;
+        InputFile {
+            name: format!("{}.png", user.handle),
+            data: png,
+            mime: "image/png".to_string(),
+        }
+        .into()
     };
 
-    let text = format!(
-        "{bio}\
-        *{tier}*, 클래스 *{class}*\n\
-        *{rank}*위, *{solve}*문제 해결, *{vote}*문제에 기여, *{rival}*명의 라이벌\n\
-        레이팅 *{rating}* \\(난이도 *{prating}* \\+ 클래스 *{crating}* \\+ 풀이 *{srating}* \\+ 기여 *{vrating}*\\)",
-        rank = rank,
-        tier = tier,
-        class = class_name,
-        rating = rating,
-        prating = problem_rating,
-        crating = class_rating,
-        srating = solve_rating,
-        vrating = vote_rating,
-        bio = bio,
-        solve = solve_count,
-        vote = vote_count,
-        rival = rival_count
-    );
+    let text = user_to_caption(&user, language);
 
     let keyboard = InlineKeyboardMarkup {
         inline_keyboard: vec![
             vec![InlineKeyboardButton {
-                text: "solved.ac 프로필".to_string(),
+                text: t.solved_ac_profile.to_string(),
                 kind: InlineKeyboardButtonKind::Url {
-                    url: format!("https://solved.ac/profile/{}", handle),
+                    url: format!("https://solved.ac/profile/{}", user.handle),
                 },
             }],
             vec![InlineKeyboardButton {
-                text: "acmicpc.net 프로필".to_string(),
+                text: t.boj_profile.to_string(),
                 kind: InlineKeyboardButtonKind::Url {
-                    url: format!("https://acmicpc.net/user/{}", handle),
+                    url: format!("https://acmicpc.net/user/{}", user.handle),
                 },
             }],
         ],
     };
 
-    let mut result = SendDocument::new(chat_id, profile)
+    Ok(SendPhoto::new(chat_id, card)
         .with_caption(text)
         .with_parse_mode(ParseMode::MarkdownV2)
-        .with_reply_markup(keyboard);
-    result.thumb = thumbnail;
-    Ok(result)
+        .with_reply_markup(keyboard))
 }
 
-fn extract_u64_or_na(map: &Map<String, Value>, key: &str) -> String {
-    map.get(key)
-        .and_then(Value::as_u64)
-        .as_ref()
-        .map_or_else(|| "N/A".to_string(), u64::to_string)
+pub fn class_stats_to_message(
+    chat_id: i64,
+    user: &User,
+    stats: &[ClassStat],
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let lines = stats
+        .iter()
+        .map(|stat| {
+            let achieved = if stat.class <= user.class {
+                "✅"
+            } else {
+                "⬜"
+            };
+            let next = if stat.essential_solved < stat.essentials {
+                fill(
+                    t.class_essentials_left,
+                    &[&(stat.essentials - stat.essential_solved)],
+                )
+            } else if stat.total_solved < stat.total {
+                fill(t.class_problems_left, &[&(stat.total - stat.total_solved)])
+            } else {
+                t.class_completed.to_string()
+            };
+            fill(
+                t.class_line,
+                &[
+                    &achieved,
+                    &stat.class,
+                    &stat.total_solved,
+                    &stat.total,
+                    &stat.essential_solved,
+                    &stat.essentials,
+                    &next,
+                ],
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = format!(
+        "{}\n\n{}",
+        fill(
+            t.class_header,
+            &[
+                &escape_markdown_v2(&user.handle),
+                &class_to_name(user.class, user.class_decoration)
+            ]
+        ),
+        lines
+    );
+
+    let mut result = SendMessage::new(chat_id, text).with_parse_mode(ParseMode::MarkdownV2);
+    let callback_data = format!("class:{}", user.handle);
+    if user.class < 10 && callback_data.len() <= 64 {
+        result = result.with_reply_markup(InlineKeyboardMarkup {
+            inline_keyboard: vec![vec![InlineKeyboardButton {
+                text: fill(t.class_essentials_button, &[&(user.class + 1)]),
+                kind: InlineKeyboardButtonKind::Callback { callback_data },
+            }]],
+        });
+    }
+    result
 }
 
-fn extract_str_or_na<'a>(map: &'a Map<String, Value>, key: &str) -> &'a str {
-    map.get(key).and_then(Value::as_str).unwrap_or("N/A")
+pub fn class_essentials_to_message(
+    chat_id: i64,
+    handle: &str,
+    class: u64,
+    problems: &[Problem],
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let text = if problems.is_empty() {
+        fill(
+            t.class_essentials_done,
+            &[&escape_markdown_v2(handle), &class],
+        )
+    } else {
+        let lines = problems
+            .iter()
+            .map(|problem| problem_to_line(problem, language))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "{}\n\n{}",
+            fill(
+                t.class_essentials_header,
+                &[&escape_markdown_v2(handle), &class]
+            ),
+            lines
+        )
+    };
+    SendMessage::new(chat_id, text)
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn problem_stats_to_message(
+    chat_id: i64,
+    handle: &str,
+    stats: &[ProblemStat],
+    chart: Vec<u8>,
+    language: Language,
+) -> SendPhoto {
+    let t = language.texts();
+    let mut groups: Vec<(&str, u64, u64)> = vec![];
+    for stat in stats {
+        if let Some(group) = level_to_group(stat.level) {
+            match groups.last_mut() {
+                Some((last, solved, total)) if *last == group => {
+                    *solved += stat.solved;
+                    *total += stat.total;
+                }
+                _ => groups.push((group, stat.solved, stat.total)),
+            }
+        }
+    }
+    let lines = groups
+        .iter()
+        .map(|(group, solved, total)| format!("{}: *{}*/{}", group, solved, total))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let solved: u64 = stats.iter().map(|stat| stat.solved).sum();
+    let text = format!(
+        "{}\n\n{}\n\n{}",
+        fill(t.stats_header, &[&escape_markdown_v2(handle)]),
+        lines,
+        fill(t.stats_total, &[&solved])
+    );
+
+    let chart = InputFile {
+        name: format!("{}.png", handle),
+        data: chart,
+        mime: "image/png".to_string(),
+    };
+    SendPhoto::new(chat_id, chart)
+        .with_caption(text)
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn rating_graph_to_message(
+    chat_id: i64,
+    handle: &str,
+    chart: Vec<u8>,
+    language: Language,
+) -> SendPhoto {
+    let chart = InputFile {
+        name: format!("{}.png", handle),
+        data: chart,
+        mime: "image/png".to_string(),
+    };
+    SendPhoto::new(chat_id, chart)
+        .with_caption(fill(
+            language.texts().graph_header,
+            &[&escape_markdown_v2(handle)],
+        ))
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+const TOP_100_PAGE_SIZE: usize = 25;
+
+fn top_100_to_text(handle: &str, problems: &[Problem], page: usize, language: Language) -> String {
+    let t = language.texts();
+    let pages = problems.len().div_ceil(TOP_100_PAGE_SIZE);
+    let cutoff = if problems.len() >= 100 {
+        let level = problems
+            .iter()
+            .map(|problem| problem.level)
+            .min()
+            .unwrap_or(0);
+        fill(
+            t.top_100_cutoff,
+            &[&level_to_name(level).unwrap_or_else(|| "N/A".to_string())],
+        )
+    } else {
+        t.top_100_not_full.to_string()
+    };
+
+    let mut text = format!(
+        "{}\n{}",
+        fill(
+            t.top_100_header,
+            &[
+                &escape_markdown_v2(handle),
+                &problems.len(),
+                &(page + 1),
+                &pages.max(1)
+            ]
+        ),
+        cutoff
+    );
+    let mut last_level = None;
+    for problem in problems
+        .iter()
+        .skip(page * TOP_100_PAGE_SIZE)
+        .take(TOP_100_PAGE_SIZE)
+    {
+        if last_level != Some(problem.level) {
+            last_level = Some(problem.level);
+            let level = level_to_name(problem.level).unwrap_or_else(|| "N/A".to_string());
+            text.push_str(&format!("\n\n*{}*", level));
+        }
+        text.push_str(&format!(
+            "\n[\\#{} {}](https://boj.kr/{0})",
+            problem.problem_id,
+            escape_markdown_v2(problem_title(problem, language))
+        ));
+    }
+    text
+}
+
+fn top_100_keyboard(
+    handle: &str,
+    problems: &[Problem],
+    page: usize,
+    language: Language,
+) -> InlineKeyboardMarkup {
+    let t = language.texts();
+    let mut buttons = vec![];
+    if page > 0 {
+        buttons.push(InlineKeyboardButton {
+            text: t.previous_page.to_string(),
+            kind: InlineKeyboardButtonKind::Callback {
+                callback_data: format!("top100:{}:{}", handle, page - 1),
+            },
+        });
+    }
+    if (page + 1) * TOP_100_PAGE_SIZE < problems.len() {
+        buttons.push(InlineKeyboardButton {
+            text: t.next_page.to_string(),
+            kind: InlineKeyboardButtonKind::Callback {
+                callback_data: format!("top100:{}:{}", handle, page + 1),
+            },
+        });
+    }
+    InlineKeyboardMarkup {
+        inline_keyboard: vec![buttons],
+    }
+}
+
+pub fn top_100_to_message(
+    chat_id: i64,
+    handle: &str,
+    problems: &[Problem],
+    language: Language,
+) -> SendMessage {
+    SendMessage::new(chat_id, top_100_to_text(handle, problems, 0, language))
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+        .with_reply_markup(top_100_keyboard(handle, problems, 0, language))
+}
+
+pub fn top_100_to_edit(
+    chat_id: i64,
+    message_id: i64,
+    handle: &str,
+    problems: &[Problem],
+    page: usize,
+    language: Language,
+) -> EditMessageText {
+    EditMessageText::new(
+        chat_id,
+        message_id,
+        top_100_to_text(handle, problems, page, language),
+    )
+    .disable_web_page_preview()
+    .with_parse_mode(ParseMode::MarkdownV2)
+    .with_reply_markup(top_100_keyboard(handle, problems, page, language))
+}
+
+fn organization_kind_to_name(kind: &str, language: Language) -> &str {
+    let t = language.texts();
+    match kind {
+        "university" => t.university,
+        "company" => t.company,
+        "community" => t.community,
+        "high_school" => t.high_school,
+        "middle_school" => t.middle_school,
+        "elementary_school" => t.elementary_school,
+        _ => kind,
+    }
+}
+
+fn user_to_ranking_line(
+    position: u64,
+    user: &User,
+    ranking: UserRanking,
+    language: Language,
+) -> String {
+    let t = language.texts();
+    let value = match ranking {
+        UserRanking::Tier | UserRanking::InOrganization(_) => format!(
+            "{} *{}*",
+            tier_to_name(user.tier).unwrap_or_else(|| "N/A".to_string()),
+            user.rating
+        ),
+        UserRanking::Class => fill(
+            t.ranking_class,
+            &[&class_to_name(user.class, user.class_decoration)],
+        ),
+        UserRanking::Streak => fill(t.ranking_streak, &[&user.max_streak]),
+    };
+    format!(
+        "{}\\. [{}](https://solved.ac/profile/{}) {}",
+        position,
+        escape_markdown_v2(&user.handle),
+        user.handle,
+        value
+    )
+}
+
+pub fn leaderboard_to_message(
+    chat_id: i64,
+    leaderboard: Leaderboard,
+    users: &[User],
+    verified: &HashSet<String>,
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let title = match leaderboard {
+        Leaderboard::Rating => t.leaderboard_rating,
+        Leaderboard::Solved => t.leaderboard_solved,
+        Leaderboard::Class => t.leaderboard_class,
+        Leaderboard::Streak => t.leaderboard_streak,
+    };
+    let lines = users
+        .iter()
+        .enumerate()
+        .map(|(i, user)| {
+            let position = match i {
+                0 => "🥇".to_string(),
+                1 => "🥈".to_string(),
+                2 => "🥉".to_string(),
+                _ => format!("{}\\.", i + 1),
+            };
+            let value = match leaderboard {
+                Leaderboard::Rating => format!(
+                    "{} *{}*",
+                    tier_to_name(user.tier).unwrap_or_else(|| "N/A".to_string()),
+                    user.rating
+                ),
+                Leaderboard::Solved => fill(t.leaderboard_solved_count, &[&user.solved_count]),
+                Leaderboard::Class => fill(
+                    t.ranking_class,
+                    &[&class_to_name(user.class, user.class_decoration)],
+                ),
+                Leaderboard::Streak => fill(t.ranking_streak, &[&user.max_streak]),
+            };
+            let mark = if verified.contains(&user.handle.to_lowercase()) {
+                String::new()
+            } else {
+                format!(" {}", t.unverified_mark)
+            };
+            format!(
+                "{} [{}](https://solved.ac/profile/{}){} {}",
+                position,
+                escape_markdown_v2(&user.handle),
+                user.handle,
+                mark,
+                value
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = if lines.is_empty() {
+        format!("*{}*\n\n{}", title, t.leaderboard_empty)
+    } else {
+        format!("*{}*\n\n{}", title, lines)
+    };
+    SendMessage::new(chat_id, text)
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn organization_to_message(
+    chat_id: i64,
+    organization: &Organization,
+    members: &[User],
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let rank = organization
+        .rank
+        .map_or_else(String::new, |rank| fill(t.organization_rank, &[&rank]));
+    let members = members
+        .iter()
+        .enumerate()
+        .map(|(i, user)| user_to_ranking_line(i as u64 + 1, user, UserRanking::Tier, language))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = format!(
+        "*{name}* \\({kind}\\)\n{summary}\n\n{members_title}\n{members}",
+        name = escape_markdown_v2(&organization.name),
+        kind = escape_markdown_v2(organization_kind_to_name(&organization.kind, language)),
+        summary = fill(
+            t.organization_summary,
+            &[
+                &organization.rating,
+                &rank,
+                &organization.user_count,
+                &organization.solved_count
+            ]
+        ),
+        members_title = t.organization_members,
+        members = members,
+    );
+
+    let keyboard = InlineKeyboardMarkup {
+        inline_keyboard: vec![vec![InlineKeyboardButton {
+            text: t.view_on_solved_ac.to_string(),
+            kind: InlineKeyboardButtonKind::Url {
+                url: format!(
+                    "https://solved.ac/ranking/o/{}",
+                    organization.organization_id
+                ),
+            },
+        }]],
+    };
+    SendMessage::new(chat_id, text)
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+        .with_reply_markup(keyboard)
+}
+
+pub fn user_ranking_to_message(
+    chat_id: i64,
+    ranking: UserRanking,
+    users: &[User],
+    offset: u64,
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let title = match ranking {
+        UserRanking::Tier => t.tier_ranking,
+        UserRanking::Class => t.class_ranking,
+        UserRanking::Streak => t.streak_ranking,
+        UserRanking::InOrganization(_) => t.organization_user_ranking,
+    };
+    let lines = users
+        .iter()
+        .enumerate()
+        .map(|(i, user)| user_to_ranking_line(offset + i as u64 + 1, user, ranking, language))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = if lines.is_empty() {
+        format!("*{}*\n\n{}", title, t.ranking_empty)
+    } else {
+        format!("*{}*\n\n{}", title, lines)
+    };
+    SendMessage::new(chat_id, text)
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn organization_ranking_to_message(
+    chat_id: i64,
+    organizations: &[Organization],
+    offset: u64,
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let lines = organizations
+        .iter()
+        .enumerate()
+        .map(|(i, organization)| {
+            format!(
+                "{}\\. [{}](https://solved.ac/ranking/o/{}) *{}* \\({}\\)",
+                offset + i as u64 + 1,
+                escape_markdown_v2(&organization.name),
+                organization.organization_id,
+                organization.rating,
+                fill(t.ranking_members, &[&organization.user_count])
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    SendMessage::new(
+        chat_id,
+        format!("*{}*\n\n{}", t.organization_ranking, lines),
+    )
+    .disable_web_page_preview()
+    .with_parse_mode(ParseMode::MarkdownV2)
 }
 
 pub fn rating_update_to_message(
     chat_id: i64,
     handle: &str,
     prev: u64,
-    current: Map<String, Value>,
+    current: &User,
 ) -> SendMessage {
-    let (rating, diff) = current.get("rating").and_then(Value::as_u64).map_or_else(
-        || ("N/A".to_string(), 0),
-        |rating| (rating.to_string(), rating as i64 - prev as i64),
+    let diff = current.rating as i64 - prev as i64;
+    let tier = tier_to_name(current.tier).unwrap_or_else(|| "N/A".to_string());
+
+    let text = if diff >= 0 {
+        format!("📈 {} = {} {} (+{})", handle, tier, current.rating, diff)
+    } else {
+        format!("📉 {} = {} {} ({})", handle, tier, current.rating, diff)
+    };
+
+    SendMessage::new(chat_id, text)
+}
+
+pub fn subscriptions_to_message(
+    chat_id: i64,
+    subscriptions: &[Subscription],
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    if subscriptions.is_empty() {
+        return SendMessage::new(chat_id, t.subscriptions_empty)
+            .with_parse_mode(ParseMode::MarkdownV2);
+    }
+    let lines = subscriptions
+        .iter()
+        .enumerate()
+        .map(|(i, subscription)| {
+            let watch = subscription.watch;
+            let mut kinds = vec![];
+            if watch.ratings {
+                kinds.push(t.watch_ratings.to_string());
+            }
+            if watch.tier {
+                kinds.push(t.watch_tier.to_string());
+            }
+            if watch.class {
+                kinds.push(t.watch_class.to_string());
+            }
+            if let Some(step) = watch.solved_step {
+                kinds.push(fill(t.watch_solved, &[&step]));
+            }
+            if watch.problems {
+                kinds.push(t.watch_problems.to_string());
+            }
+            format!(
+                "{}\\. [{}](https://solved.ac/profile/{}) *{}* \\({}\\)",
+                i + 1,
+                escape_markdown_v2(&subscription.target),
+                subscription.target,
+                subscription.rating,
+                escape_markdown_v2(&kinds.join(", "))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    SendMessage::new(
+        chat_id,
+        format!("*{}*\n\n{}", t.subscriptions_header, lines),
+    )
+    .disable_web_page_preview()
+    .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn arena_to_message(chat_id: i64, user: &User, language: Language) -> SendMessage {
+    let t = language.texts();
+    let profile = format!(
+        "[{}](https://solved.ac/profile/{})",
+        escape_markdown_v2(&user.handle),
+        user.handle
     );
-    let tier = current
-        .get("tier")
-        .and_then(Value::as_u64)
-        .and_then(tier_to_name)
-        .unwrap_or_else(|| "N/A".to_string());
+    let text = if user.arena_competed_round_count == 0 {
+        fill(t.arena_not_joined, &[&profile])
+    } else {
+        format!(
+            "{profile} {arena}\n{summary}\n{rounds}",
+            profile = profile,
+            arena = t.arena,
+            summary = fill(
+                t.arena_summary,
+                &[
+                    &escape_markdown_v2(arena_tier_to_name(user.arena_tier).unwrap_or("N/A")),
+                    &user.arena_rating,
+                    &escape_markdown_v2(arena_tier_to_name(user.arena_max_tier).unwrap_or("N/A")),
+                    &user.arena_max_rating,
+                ]
+            ),
+            rounds = fill(t.arena_rounds, &[&user.arena_competed_round_count]),
+        )
+    };
+    SendMessage::new(chat_id, text)
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn arena_rating_update_to_message(
+    chat_id: i64,
+    handle: &str,
+    prev: u64,
+    current: &User,
+    language: Language,
+) -> SendMessage {
+    let diff = current.arena_rating as i64 - prev as i64;
+    let arena = language.texts().arena;
+    let tier = arena_tier_to_name(current.arena_tier).unwrap_or("N/A");
 
     let text = if diff >= 0 {
-        format!("📈 {} = {} {} (+{})", handle, tier, rating, diff)
+        format!(
+            "📈 {} = {} {} {} (+{})",
+            handle, arena, tier, current.arena_rating, diff
+        )
     } else {
-        format!("📉 {} = {} {} ({})", handle, tier, rating, diff)
+        format!(
+            "📉 {} = {} {} {} ({})",
+            handle, arena, tier, current.arena_rating, diff
+        )
     };
 
     SendMessage::new(chat_id, text)
 }
+
+pub fn tier_update_to_message(
+    chat_id: i64,
+    handle: &str,
+    prev: u64,
+    current: &User,
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let template = if current.tier > prev {
+        t.tier_promoted
+    } else {
+        t.tier_demoted
+    };
+    let tier = tier_to_name(current.tier).unwrap_or_else(|| "N/A".to_string());
+    let text = fill(
+        template,
+        &[&escape_markdown_v2(handle), &escape_markdown_v2(&tier)],
+    );
+    SendMessage::new(chat_id, text).with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn class_update_to_message(
+    chat_id: i64,
+    handle: &str,
+    current: &User,
+    language: Language,
+) -> SendMessage {
+    let text = fill(
+        language.texts().class_achieved,
+        &[
+            &escape_markdown_v2(handle),
+            &class_to_name(current.class, current.class_decoration),
+        ],
+    );
+    SendMessage::new(chat_id, text).with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn solved_milestone_to_message(
+    chat_id: i64,
+    handle: &str,
+    milestone: u64,
+    language: Language,
+) -> SendMessage {
+    let text = fill(
+        language.texts().solved_milestone,
+        &[&escape_markdown_v2(handle), &milestone],
+    );
+    SendMessage::new(chat_id, text).with_parse_mode(ParseMode::MarkdownV2)
+}
+
+/// Lists the new solves found in the top 100, and counts those that did not make it there.
+pub fn new_solves_to_message(
+    chat_id: i64,
+    handle: &str,
+    problems: &[&Problem],
+    more: u64,
+    language: Language,
+) -> SendMessage {
+    let t = language.texts();
+    let mut lines = vec![fill(t.new_solves, &[&escape_markdown_v2(handle)])];
+    lines.extend(
+        problems
+            .iter()
+            .map(|problem| problem_to_line(problem, language)),
+    );
+    if more > 0 {
+        lines.push(fill(t.new_solves_more, &[&more]));
+    }
+    SendMessage::new(chat_id, lines.join("\n"))
+        .disable_web_page_preview()
+        .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+/// Lists each handle with its rating at the start and the end of the week.
+pub fn weekly_digest_to_message(
+    chat_id: i64,
+    changes: &[(String, u64, u64)],
+    language: Language,
+) -> SendMessage {
+    let lines = changes
+        .iter()
+        .enumerate()
+        .map(|(i, (handle, start, end))| {
+            let diff = *end as i64 - *start as i64;
+            format!(
+                "{}\\. [{}](https://solved.ac/profile/{}) {} → *{}* \\({}\\)",
+                i + 1,
+                escape_markdown_v2(handle),
+                handle,
+                start,
+                end,
+                escape_markdown_v2(&format!("{:+}", diff))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    SendMessage::new(
+        chat_id,
+        format!("*{}*\n\n{}", language.texts().digest_header, lines),
+    )
+    .disable_web_page_preview()
+    .with_parse_mode(ParseMode::MarkdownV2)
+}
+
+pub fn solved_error_to_message(
+    chat_id: i64,
+    error: &SolvedError,
+    language: Language,
+) -> SendMessage {
+    SendMessage::new(chat_id, solved_error_to_text(error, language))
+}
+
+pub fn solved_error_to_text(error: &SolvedError, language: Language) -> String {
+    let t = language.texts();
+    match error {
+        SolvedError::NotFound => t.error_not_found.to_string(),
+        SolvedError::RateLimited {
+            retry_after: Some(seconds),
+        } => fill(t.error_rate_limited_for, &[seconds]),
+        SolvedError::RateLimited { retry_after: None } => t.error_rate_limited.to_string(),
+        SolvedError::Upstream(status) => fill(t.error_upstream, &[status]),
+        SolvedError::Decode(_) => t.error_decode.to_string(),
+        SolvedError::Transport(_) => t.error_transport.to_string(),
+        SolvedError::BudgetExhausted => t.error_budget_exhausted.to_string(),
+    }
+}
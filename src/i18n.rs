@@ -0,0 +1,535 @@
+use std::fmt::{self, Write};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    Korean,
+    English,
+}
+
+impl Language {
+    /// Accepts IETF language tags such as `en-US`, as sent by Telegram clients.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.split(['-', '_'].as_ref()).next()? {
+            "ko" => Some(Language::Korean),
+            "en" => Some(Language::English),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Korean => "ko",
+            Language::English => "en",
+        }
+    }
+
+    pub fn texts(self) -> &'static Texts {
+        match self {
+            Language::Korean => &KOREAN,
+            Language::English => &ENGLISH,
+        }
+    }
+}
+
+/// Replaces each `{}` in the template with the next argument, and each `{n}` with the n-th one.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some((start, end)) = rest
+        .find('{')
+        .and_then(|start| Some((start, start + rest[start..].find('}')?)))
+    {
+        result.push_str(&rest[..start]);
+        let index = match &rest[start + 1..end] {
+            "" => {
+                next += 1;
+                Some(next - 1)
+            }
+            index => index.parse().ok(),
+        };
+        match index.and_then(|index: usize| args.get(index)) {
+            Some(arg) => {
+                let _ = write!(result, "{}", arg);
+            }
+            None => result.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Every user-facing text of the bot. Texts sent with MarkdownV2 are already escaped, and `{}`
+/// or `{n}` marks the arguments given to [`fill`].
+pub struct Texts {
+    pub language_changed: &'static str,
+
+    pub usage_problem: &'static str,
+    pub usage_user: &'static str,
+    pub usage_class: &'static str,
+    pub usage_stats: &'static str,
+    pub usage_top_100: &'static str,
+    pub usage_org: &'static str,
+    pub usage_ranking: &'static str,
+    pub usage_arena: &'static str,
+    pub usage_subscribe: &'static str,
+    pub usage_unsubscribe: &'static str,
+    pub usage_lang: &'static str,
+    pub usage_link: &'static str,
+    pub usage_verify: &'static str,
+    pub usage_verified_only: &'static str,
+    pub usage_graph: &'static str,
+    pub usage_tag: &'static str,
+    pub usage_join: &'static str,
+    pub usage_leaderboard: &'static str,
+    pub usage_daily: &'static str,
+
+    pub problem_not_found: &'static str,
+    pub no_matching_problem: &'static str,
+    pub user_not_found: &'static str,
+    pub tag_not_found: &'static str,
+    pub organization_not_found: &'static str,
+    pub subscribed: &'static str,
+    pub subscribed_tier: &'static str,
+    pub subscribed_class: &'static str,
+    pub subscribed_solved: &'static str,
+    pub subscribed_problems: &'static str,
+    pub unsubscribed: &'static str,
+    pub not_subscribed: &'static str,
+    pub subscriptions_header: &'static str,
+    pub subscriptions_empty: &'static str,
+    pub watch_ratings: &'static str,
+    pub watch_tier: &'static str,
+    pub watch_class: &'static str,
+    pub watch_solved: &'static str,
+    pub watch_problems: &'static str,
+    pub linked: &'static str,
+    pub unlinked: &'static str,
+    pub not_linked: &'static str,
+    pub verification_started: &'static str,
+    pub verification_failed: &'static str,
+    pub verified: &'static str,
+    pub joined: &'static str,
+    pub members_full: &'static str,
+    pub handle_taken: &'static str,
+    pub verification_required: &'static str,
+    pub verified_only_on: &'static str,
+    pub verified_only_off: &'static str,
+    pub unverified_mark: &'static str,
+    pub left: &'static str,
+    pub not_member: &'static str,
+    pub admin_only: &'static str,
+    pub admin_required: &'static str,
+    pub daily_on: &'static str,
+    pub daily_pinned: &'static str,
+    pub daily_off: &'static str,
+    pub daily_not_set: &'static str,
+
+    pub partial: &'static str,
+    pub not_solvable: &'static str,
+    pub sprout: &'static str,
+    pub level_locked: &'static str,
+    pub problem_summary: &'static str,
+    pub problem_count: &'static str,
+    pub problem_count_plain: &'static str,
+    pub tags: &'static str,
+    pub aliases: &'static str,
+    pub other_tags: &'static str,
+    pub tag_problems_button: &'static str,
+    pub view_on_boj: &'static str,
+    pub view_on_solved_ac: &'static str,
+    pub reroll: &'static str,
+    pub daily_header: &'static str,
+
+    pub user_tier_class: &'static str,
+    pub user_summary: &'static str,
+    pub user_rating: &'static str,
+    pub user_description: &'static str,
+    pub solved_ac_profile: &'static str,
+    pub boj_profile: &'static str,
+
+    pub class_header: &'static str,
+    pub class_line: &'static str,
+    pub class_essentials_left: &'static str,
+    pub class_problems_left: &'static str,
+    pub class_completed: &'static str,
+    pub class_essentials_button: &'static str,
+    pub class_essentials_done: &'static str,
+    pub class_essentials_header: &'static str,
+
+    pub stats_header: &'static str,
+    pub stats_total: &'static str,
+
+    pub graph_header: &'static str,
+    pub graph_empty: &'static str,
+
+    pub top_100_header: &'static str,
+    pub top_100_cutoff: &'static str,
+    pub top_100_not_full: &'static str,
+    pub previous_page: &'static str,
+    pub next_page: &'static str,
+
+    pub organization_summary: &'static str,
+    pub organization_rank: &'static str,
+    pub organization_members: &'static str,
+    pub university: &'static str,
+    pub company: &'static str,
+    pub community: &'static str,
+    pub high_school: &'static str,
+    pub middle_school: &'static str,
+    pub elementary_school: &'static str,
+
+    pub tier_ranking: &'static str,
+    pub class_ranking: &'static str,
+    pub streak_ranking: &'static str,
+    pub organization_user_ranking: &'static str,
+    pub organization_ranking: &'static str,
+    pub ranking_empty: &'static str,
+    pub ranking_class: &'static str,
+    pub ranking_streak: &'static str,
+    pub ranking_members: &'static str,
+
+    pub leaderboard_rating: &'static str,
+    pub leaderboard_solved: &'static str,
+    pub leaderboard_class: &'static str,
+    pub leaderboard_streak: &'static str,
+    pub leaderboard_solved_count: &'static str,
+    pub leaderboard_empty: &'static str,
+
+    pub arena: &'static str,
+    pub arena_not_joined: &'static str,
+    pub arena_summary: &'static str,
+    pub arena_rounds: &'static str,
+
+    pub tier_promoted: &'static str,
+    pub tier_demoted: &'static str,
+    pub class_achieved: &'static str,
+    pub solved_milestone: &'static str,
+    pub new_solves: &'static str,
+    pub new_solves_more: &'static str,
+    pub digest_header: &'static str,
+
+    pub error_not_found: &'static str,
+    pub error_rate_limited_for: &'static str,
+    pub error_rate_limited: &'static str,
+    pub error_upstream: &'static str,
+    pub error_decode: &'static str,
+    pub error_transport: &'static str,
+    pub error_budget_exhausted: &'static str,
+}
+
+static KOREAN: Texts = Texts {
+    language_changed: "언어를 한국어로 설정했습니다.",
+
+    usage_problem: "사용법: /problem <문제번호1> <문제번호2> <...>",
+    usage_user: "사용법: /user [사용자명]\n/link로 계정을 연결하면 사용자명을 생략할 수 있습니다.",
+    usage_class: "사용법: /class <사용자명> [essentials]",
+    usage_stats: "사용법: /stats [사용자명]\n/link로 계정을 연결하면 사용자명을 생략할 수 있습니다.",
+    usage_top_100: "사용법: /top100 <사용자명>",
+    usage_org: "사용법: /org <단체명 또는 ID>",
+    usage_ranking: "사용법: /ranking [tier|class|streak|org <단체 ID>] [페이지]",
+    usage_arena: "사용법: /arena <사용자명>",
+    usage_subscribe: "사용법: /subscribe <ratings|tier|class|solved|problems> [사용자명]\nratings는 레이팅, tier는 티어 승급과 강등, class는 클래스 달성, problems는 새로 해결한 문제를 알려줍니다.\n/subscribe solved [사용자명] [단위]는 해결한 문제 수가 단위(기본 100)의 배수가 될 때마다 알려줍니다. 숫자로 된 사용자명은 /subscribe solved 1234 100처럼 단위와 함께 입력해 주세요.\n/link로 계정을 연결하면 사용자명을 생략할 수 있습니다.",
+    usage_unsubscribe: "사용법: /unsubscribe <ratings|tier|class|solved|problems|all> <사용자명>\n구독 중인 사용자는 /subscriptions로 확인할 수 있습니다.",
+    usage_lang: "사용법: /lang <ko|en>",
+    usage_link: "사용법: /link <사용자명>",
+    usage_verify: "사용법: /verify [사용자명]",
+    usage_verified_only: "사용법: /verifiedonly <on|off>\n켜면 /verify로 인증한 계정으로만 리더보드에 참가할 수 있습니다.",
+    usage_graph: "사용법: /graph [사용자명] [기간]\n기간은 30d, 12w, 6m, 1y처럼 입력하며 생략하면 전체 기록을 보여줍니다.\n30d처럼 기간 모양의 사용자명은 /graph 30d 1y처럼 기간과 함께 입력해 주세요.",
+    usage_tag: "사용법: /tag <키워드\\>",
+    usage_join: "사용법: /join [사용자명]\n/link로 계정을 연결하면 사용자명을 생략할 수 있습니다.\n/leave로 리더보드에서 빠지고, 관리자는 /leave <사용자명>으로 다른 참가자를 뺄 수 있습니다.",
+    usage_leaderboard: "사용법: /leaderboard [rating|solved|class|streak]\n/join으로 참가한 사용자의 순위를 보여줍니다.",
+    usage_daily: "사용법: /daily on <검색어> <HH:MM> [pin]\n/daily off\n매일 정해진 시각(한국 시간)에 검색어에 맞는 문제를 하나 올리며, 90일 안에 올린 문제는 다시 올리지 않습니다. pin을 붙이면 올린 문제를 고정합니다.",
+
+    problem_not_found: "문제를 찾을 수 없습니다.",
+    no_matching_problem: "조건에 맞는 문제가 없습니다.",
+    user_not_found: "사용자를 찾을 수 없습니다.",
+    tag_not_found: "태그를 찾을 수 없습니다.",
+    organization_not_found: "단체를 찾을 수 없습니다.",
+    subscribed: "*{}*님의 레이팅과 아레나 레이팅 변화를 구독했습니다\\.",
+    subscribed_tier: "*{}*님의 티어 승급과 강등을 구독했습니다\\.",
+    subscribed_class: "*{}*님의 클래스 달성을 구독했습니다\\.",
+    subscribed_solved: "*{0}*님이 해결한 문제 수가 {1}의 배수가 될 때마다 알려드립니다\\.",
+    subscribed_problems: "*{}*님이 새로운 문제를 해결할 때마다 알려드립니다\\.",
+    unsubscribed: "*{}*님에 대한 알림 구독을 취소했습니다\\.",
+    not_subscribed: "*{}*님에 대한 해당 알림을 구독하고 있지 않습니다\\.",
+    subscriptions_header: "구독 중인 사용자",
+    subscriptions_empty: "구독 중인 사용자가 없습니다\\. /subscribe ratings \\[사용자명\\]으로 구독할 수 있습니다\\.",
+    watch_ratings: "레이팅",
+    watch_tier: "티어",
+    watch_class: "클래스",
+    watch_solved: "{}문제마다",
+    watch_problems: "새로 해결한 문제",
+    linked: "{} 계정을 연결했습니다.",
+    unlinked: "계정 연결을 해제했습니다.",
+    not_linked: "연결된 계정이 없습니다. /link <사용자명>으로 계정을 연결해 주세요.",
+    verification_started: "*{}* 계정의 [solved\\.ac 자기소개](https://solved.ac/settings/profile)에 아래 토큰을 추가한 다음 /verify를 다시 보내 주세요\\. 토큰은 1시간 동안 유효합니다\\.\n\n`{}`",
+    verification_failed: "자기소개에서 토큰 {}을 찾지 못했습니다. 자기소개를 저장한 다음 다시 시도해 주세요.",
+    verified: "{} 계정을 인증했습니다.",
+    joined: "*{}* 계정으로 리더보드에 참가했습니다\\.",
+    members_full: "리더보드에는 최대 {}명까지 참가할 수 있습니다.",
+    handle_taken: "*{}* 계정으로는 이미 다른 사용자가 참가했습니다\\.",
+    verification_required: "이 채팅에서는 /link로 연결하고 /verify로 인증한 계정으로만 참가할 수 있습니다.",
+    verified_only_on: "이제 인증한 계정으로만 리더보드에 참가할 수 있습니다.",
+    verified_only_off: "이제 인증하지 않은 계정으로도 리더보드에 참가할 수 있습니다.",
+    unverified_mark: "\\(미인증\\)",
+    left: "*{}*님이 리더보드에서 빠졌습니다\\.",
+    not_member: "리더보드에 참가한 사용자가 아닙니다\\.",
+    admin_only: "다른 참가자는 관리자만 뺄 수 있습니다.",
+    admin_required: "관리자만 설정할 수 있습니다.",
+    daily_on: "매일 {0}(한국 시간)에 \"{1}\" 조건의 문제를 올립니다.",
+    daily_pinned: "올린 문제는 고정됩니다.",
+    daily_off: "오늘의 문제를 더 이상 올리지 않습니다.",
+    daily_not_set: "오늘의 문제가 설정되어 있지 않습니다.",
+
+    partial: "부분 점수 / 서브태스크",
+    not_solvable: "채점 준비중",
+    sprout: "🌱 새싹 문제",
+    level_locked: "🔒 난이도 고정",
+    problem_summary: "*{}*명 해결, 평균 *{}*회 시도",
+    problem_count: "*{}*문제",
+    problem_count_plain: "{}문제",
+    tags: "태그: {}",
+    aliases: "별칭: {}",
+    other_tags: "다른 태그: {}",
+    tag_problems_button: "난이도순 문제 보기",
+    view_on_boj: "acmicpc.net에서 보기",
+    view_on_solved_ac: "solved.ac에서 보기",
+    reroll: "다시 뽑기",
+    daily_header: "📅 오늘의 문제",
+
+    user_tier_class: "*{}*, 클래스 *{}*",
+    user_summary: "*{}*위, *{}*문제 해결, *{}*문제에 기여, *{}*명의 라이벌",
+    user_rating: "레이팅 *{}* \\(난이도 *{}* \\+ 클래스 *{}* \\+ 풀이 *{}* \\+ 기여 *{}*\\)",
+    user_description: "{} · 레이팅 {}",
+    solved_ac_profile: "solved.ac 프로필",
+    boj_profile: "acmicpc.net 프로필",
+
+    class_header: "*{}* 클래스 *{}*",
+    class_line: "{} 클래스 {}: 전체 {}/{}, 에센셜 {}/{} → {}",
+    class_essentials_left: "\\+까지 에센셜 *{}*문제",
+    class_problems_left: "\\+\\+까지 *{}*문제",
+    class_completed: "\\+\\+ 달성",
+    class_essentials_button: "클래스 {} 남은 에센셜 보기",
+    class_essentials_done: "*{}*님은 클래스 {}의 에센셜 문제를 모두 해결했습니다\\.",
+    class_essentials_header: "*{}*님이 풀지 않은 클래스 {} 에센셜 문제",
+
+    stats_header: "*{}*님의 난이도별 해결 문제",
+    stats_total: "합계 *{}*문제",
+
+    graph_header: "*{}*님의 레이팅 변화",
+    graph_empty: "기록된 레이팅이 없습니다. 레이팅은 /subscribe ratings로 구독한 사용자만 기록됩니다.",
+
+    top_100_header: "*{}*님의 상위 {}문제 \\({}/{}\\)",
+    top_100_cutoff: "*{}*보다 어려운 문제를 풀어야 난이도 레이팅이 오릅니다\\.",
+    top_100_not_full: "아직 100문제를 채우지 않아 어떤 문제를 풀어도 난이도 레이팅이 오릅니다\\.",
+    previous_page: "◀ 이전",
+    next_page: "다음 ▶",
+
+    organization_summary: "레이팅 *{}*{}, *{}*명, *{}*문제 해결",
+    organization_rank: ", *{}*위",
+    organization_members: "상위 멤버",
+    university: "대학교",
+    company: "회사",
+    community: "커뮤니티",
+    high_school: "고등학교",
+    middle_school: "중학교",
+    elementary_school: "초등학교",
+
+    tier_ranking: "레이팅 랭킹",
+    class_ranking: "클래스 랭킹",
+    streak_ranking: "최장 스트릭 랭킹",
+    organization_user_ranking: "단체 내 랭킹",
+    organization_ranking: "단체 랭킹",
+    ranking_empty: "순위가 없습니다\\.",
+    ranking_class: "클래스 *{}*",
+    ranking_streak: "최장 *{}*일 연속",
+    ranking_members: "{}명",
+
+    leaderboard_rating: "레이팅 리더보드",
+    leaderboard_solved: "해결 문제 리더보드",
+    leaderboard_class: "클래스 리더보드",
+    leaderboard_streak: "최장 스트릭 리더보드",
+    leaderboard_solved_count: "*{}*문제",
+    leaderboard_empty: "아직 참가한 사용자가 없습니다\\. /join \\[사용자명\\]으로 참가할 수 있습니다\\.",
+
+    arena: "아레나",
+    arena_not_joined: "{}님은 아직 아레나에 참가하지 않았습니다\\.",
+    arena_summary: "*{}* *{}*, 최고 *{}* *{}*",
+    arena_rounds: "*{}*회 참가",
+
+    tier_promoted: "🎉 *{0}*님이 *{1}*\\(으\\)로 승급했습니다\\!",
+    tier_demoted: "📉 *{0}*님의 티어가 *{1}*\\(으\\)로 내려갔습니다\\.",
+    class_achieved: "🎓 *{0}*님이 *CLASS {1}*을 달성했습니다\\!",
+    solved_milestone: "🏆 *{0}*님이 *{1}*문제를 해결했습니다\\!",
+    new_solves: "✅ *{}*님이 새로운 문제를 해결했습니다\\!",
+    new_solves_more: "외 *{}*문제",
+    digest_header: "📊 이번 주 레이팅 변화",
+
+    error_not_found: "solved.ac에서 찾을 수 없습니다.",
+    error_rate_limited_for: "solved.ac 요청 한도를 초과했습니다. {}초 후에 다시 시도해 주세요.",
+    error_rate_limited: "solved.ac 요청 한도를 초과했습니다. 잠시 후 다시 시도해 주세요.",
+    error_upstream: "solved.ac 서버가 응답하지 않습니다. (HTTP {})",
+    error_decode: "solved.ac 응답을 해석하지 못했습니다.",
+    error_transport: "solved.ac에 연결하지 못했습니다.",
+    error_budget_exhausted: "요청이 너무 많습니다. 잠시 후 다시 시도해 주세요.",
+};
+
+static ENGLISH: Texts = Texts {
+    language_changed: "Language set to English.",
+
+    usage_problem: "Usage: /problem <id1> <id2> <...>",
+    usage_user: "Usage: /user [handle]\nThe handle can be omitted after linking an account with /link.",
+    usage_class: "Usage: /class <handle> [essentials]",
+    usage_stats: "Usage: /stats [handle]\nThe handle can be omitted after linking an account with /link.",
+    usage_top_100: "Usage: /top100 <handle>",
+    usage_org: "Usage: /org <name or ID>",
+    usage_ranking: "Usage: /ranking [tier|class|streak|org <organization ID>] [page]",
+    usage_arena: "Usage: /arena <handle>",
+    usage_subscribe: "Usage: /subscribe <ratings|tier|class|solved|problems> [handle]\nratings follows rating changes, tier follows promotions and demotions, class follows class achievements, and problems follows newly solved problems.\n/subscribe solved [handle] [step] notifies whenever the solved count reaches a multiple of the step, 100 by default. Give numeric handles along with a step, as in /subscribe solved 1234 100.\nThe handle can be omitted after linking an account with /link.",
+    usage_unsubscribe: "Usage: /unsubscribe <ratings|tier|class|solved|problems|all> <handle>\nSee the users you follow with /subscriptions.",
+    usage_lang: "Usage: /lang <ko|en>",
+    usage_link: "Usage: /link <handle>",
+    usage_verify: "Usage: /verify [handle]",
+    usage_verified_only: "Usage: /verifiedonly <on|off>\nWhen on, only handles verified with /verify can join the leaderboard.",
+    usage_graph: "Usage: /graph [handle] [period]\nPeriods look like 30d, 12w, 6m or 1y, and the whole history is shown without one.\nGive handles that look like periods along with a period, as in /graph 30d 1y.",
+    usage_tag: "Usage: /tag <keyword\\>",
+    usage_join: "Usage: /join [handle]\nThe handle can be omitted after linking an account with /link.\nLeave with /leave, and administrators can remove others with /leave <handle>.",
+    usage_leaderboard: "Usage: /leaderboard [rating|solved|class|streak]\nRanks the users who joined with /join.",
+    usage_daily: "Usage: /daily on <query> <HH:MM> [pin]\n/daily off\nPosts a problem matching the query every day at the time in KST, without repeating problems within 90 days. Add pin to pin the posts.",
+
+    problem_not_found: "Problem not found.",
+    no_matching_problem: "No problem matches the query.",
+    user_not_found: "User not found.",
+    tag_not_found: "Tag not found.",
+    organization_not_found: "Organization not found.",
+    subscribed: "Subscribed to rating and arena rating changes of *{}*\\.",
+    subscribed_tier: "Subscribed to tier promotions and demotions of *{}*\\.",
+    subscribed_class: "Subscribed to class achievements of *{}*\\.",
+    subscribed_solved: "You will be notified whenever the solved count of *{0}* reaches a multiple of {1}\\.",
+    subscribed_problems: "You will be notified whenever *{}* solves a new problem\\.",
+    unsubscribed: "Stopped the notifications about *{}*\\.",
+    not_subscribed: "This chat does not have those notifications about *{}*\\.",
+    subscriptions_header: "Subscriptions",
+    subscriptions_empty: "This chat has no subscriptions\\. Subscribe with /subscribe ratings \\[handle\\]\\.",
+    watch_ratings: "ratings",
+    watch_tier: "tier",
+    watch_class: "class",
+    watch_solved: "every {} solved",
+    watch_problems: "new solves",
+    linked: "Linked to {}.",
+    unlinked: "Unlinked your account.",
+    not_linked: "No account is linked. Link one with /link <handle>.",
+    verification_started: "Add the token below to the [solved\\.ac bio](https://solved.ac/settings/profile) of *{}*, then send /verify again\\. The token is valid for an hour\\.\n\n`{}`",
+    verification_failed: "Could not find the token {} in the bio. Save the bio and try again.",
+    verified: "Verified the ownership of {}.",
+    joined: "Joined the leaderboard as *{}*\\.",
+    members_full: "Up to {} members can join the leaderboard.",
+    handle_taken: "Someone else has already joined as *{}*\\.",
+    verification_required: "Only handles linked with /link and verified with /verify can join in this chat.",
+    verified_only_on: "Only verified handles can join the leaderboard now.",
+    verified_only_off: "Unverified handles can join the leaderboard now.",
+    unverified_mark: "\\(unverified\\)",
+    left: "*{}* left the leaderboard\\.",
+    not_member: "Not on the leaderboard\\.",
+    admin_only: "Only administrators can remove other members.",
+    admin_required: "Only administrators can change this.",
+    daily_on: "A problem matching \"{1}\" will be posted every day at {0} KST.",
+    daily_pinned: "The posts will be pinned.",
+    daily_off: "Stopped posting daily problems.",
+    daily_not_set: "Daily problems are not set up.",
+
+    partial: "Partial score / Subtasks",
+    not_solvable: "Not judged yet",
+    sprout: "🌱 Sprout",
+    level_locked: "🔒 Level locked",
+    problem_summary: "Solved by *{}*, *{}* tries on average",
+    problem_count: "*{}* problems",
+    problem_count_plain: "{} problems",
+    tags: "Tags: {}",
+    aliases: "Aliases: {}",
+    other_tags: "Other tags: {}",
+    tag_problems_button: "Problems by level",
+    view_on_boj: "View on acmicpc.net",
+    view_on_solved_ac: "View on solved.ac",
+    reroll: "Pick another",
+    daily_header: "📅 Problem of the day",
+
+    user_tier_class: "*{}*, Class *{}*",
+    user_summary: "Rank *{}*, *{}* solved, *{}* contributed, *{}* rivals",
+    user_rating: "Rating *{}* \\(problems *{}* \\+ class *{}* \\+ solved *{}* \\+ votes *{}*\\)",
+    user_description: "{} · Rating {}",
+    solved_ac_profile: "solved.ac profile",
+    boj_profile: "acmicpc.net profile",
+
+    class_header: "*{}* Class *{}*",
+    class_line: "{} Class {}: total {}/{}, essentials {}/{} → {}",
+    class_essentials_left: "*{}* essentials to \\+",
+    class_problems_left: "*{}* problems to \\+\\+",
+    class_completed: "\\+\\+ achieved",
+    class_essentials_button: "Remaining class {} essentials",
+    class_essentials_done: "*{}* solved every essential problem of class {}\\.",
+    class_essentials_header: "Class {1} essentials *{0}* has not solved",
+
+    stats_header: "Problems solved by *{}* per level",
+    stats_total: "Total *{}*",
+
+    graph_header: "Rating history of *{}*",
+    graph_empty: "No rating is recorded. Ratings are recorded only for users subscribed with /subscribe ratings.",
+
+    top_100_header: "Top {1} problems of *{0}* \\({2}/{3}\\)",
+    top_100_cutoff: "Solve problems harder than *{}* to raise the problem rating\\.",
+    top_100_not_full: "Every solve raises the problem rating until the top 100 is full\\.",
+    previous_page: "◀ Previous",
+    next_page: "Next ▶",
+
+    organization_summary: "Rating *{}*{}, *{}* members, *{}* solved",
+    organization_rank: ", rank *{}*",
+    organization_members: "Top members",
+    university: "University",
+    company: "Company",
+    community: "Community",
+    high_school: "High school",
+    middle_school: "Middle school",
+    elementary_school: "Elementary school",
+
+    tier_ranking: "Rating ranking",
+    class_ranking: "Class ranking",
+    streak_ranking: "Longest streak ranking",
+    organization_user_ranking: "Organization ranking of users",
+    organization_ranking: "Organization ranking",
+    ranking_empty: "No one is ranked\\.",
+    ranking_class: "Class *{}*",
+    ranking_streak: "Longest streak *{}* days",
+    ranking_members: "{} members",
+
+    leaderboard_rating: "Rating leaderboard",
+    leaderboard_solved: "Solved leaderboard",
+    leaderboard_class: "Class leaderboard",
+    leaderboard_streak: "Longest streak leaderboard",
+    leaderboard_solved_count: "*{}* solved",
+    leaderboard_empty: "No one has joined yet\\. Join with /join \\[handle\\]\\.",
+
+    arena: "Arena",
+    arena_not_joined: "{} has not competed in the arena yet\\.",
+    arena_summary: "*{}* *{}*, best *{}* *{}*",
+    arena_rounds: "*{}* rounds",
+
+    tier_promoted: "🎉 *{0}* has been promoted to *{1}*\\!",
+    tier_demoted: "📉 *{0}* has dropped to *{1}*\\.",
+    class_achieved: "🎓 *{0}* has achieved *CLASS {1}*\\!",
+    solved_milestone: "🏆 *{0}* has solved *{1}* problems\\!",
+    new_solves: "✅ *{}* solved new problems\\!",
+    new_solves_more: "and *{}* more",
+    digest_header: "📊 Rating changes this week",
+
+    error_not_found: "Not found on solved.ac.",
+    error_rate_limited_for: "Too many requests to solved.ac. Please try again in {} seconds.",
+    error_rate_limited: "Too many requests to solved.ac. Please try again later.",
+    error_upstream: "solved.ac is not responding. (HTTP {})",
+    error_decode: "Could not read the response from solved.ac.",
+    error_transport: "Could not connect to solved.ac.",
+    error_budget_exhausted: "Too many requests. Please try again later.",
+};
@@ -1,256 +1,1686 @@
 use db::ProfileImages;
-use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::mem;
 use std::result::Result;
+use telbot_cf_worker::types::chat::{ChatMember, GetChatMember, PinChatMessage};
 use telbot_cf_worker::types::markup::ParseMode;
 use telbot_cf_worker::types::message::{Message, MessageKind, SendMessage};
-use telbot_cf_worker::types::query::AnswerInlineQuery;
+use telbot_cf_worker::types::query::{
+    AnswerCallbackQuery, AnswerInlineQuery, CallbackQuery, InlineQuery,
+};
 use telbot_cf_worker::types::update::*;
 use telbot_cf_worker::Api;
-use worker::js_sys::{Number, RegExp};
+use worker::js_sys::{Math, Number, RegExp};
 use worker::*;
 
-use crate::command::Command;
-use crate::db::RatingAlarms;
+use crate::command::{parse_period, parse_step, parse_time, random_query, Command};
+use crate::db::{
+    Change, ChatMembers, ChatSettings, DailyConfig, DailyProblems, Join, LinkedHandles,
+    PendingVerification, RandomQueries, RatingHistory, ResponseCache, Subscription, Subscriptions,
+};
+use crate::i18n::{fill, Language};
+use crate::solved::{CacheStats, ProblemSort, SolvedError, UserRanking};
 
+mod card;
+mod chart;
 mod command;
 mod db;
+mod font;
 mod formatter;
-mod solved;
+mod i18n;
+pub mod solved;
 mod utils;
 
-fn log_request(req: &Request) {
-    console_log!(
+/// Solved-count milestones of `/subscribe solved` without a step.
+const DEFAULT_SOLVED_STEP: u64 = 100;
+
+/// Caps `/leaderboard` within the request budget of a single invocation.
+const MAX_MEMBERS: usize = 30;
+
+#[derive(Clone, Copy)]
+enum Leaderboard {
+    Rating,
+    Solved,
+    Class,
+    Streak,
+}
+
+impl Leaderboard {
+    fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "rating" => Some(Self::Rating),
+            "solved" => Some(Self::Solved),
+            "class" => Some(Self::Class),
+            "streak" => Some(Self::Streak),
+            _ => None,
+        }
+    }
+
+    /// Sorts the best first, breaking ties by rating.
+    fn sort(self, users: &mut [solved::User]) {
+        users.sort_by_key(|user| {
+            let key = match self {
+                Self::Rating => (user.rating, 0),
+                Self::Solved => (user.solved_count, 0),
+                Self::Class => (user.class, user.class_decoration as u64),
+                Self::Streak => (user.max_streak, 0),
+            };
+            Reverse((key, user.rating))
+        });
+    }
+}
+
+/// Jobs run by the cron triggers in `wrangler.toml`, or by `POST /jobs/{name}` with the
+/// `JOB_SECRET` as a bearer token.
+#[derive(Clone, Copy)]
+enum Job {
+    Ratings,
+    Daily,
+    Digest,
+}
+
+impl Job {
+    fn from_cron(cron: &str) -> Option<Self> {
+        match cron {
+            "*/10 * * * *" => Some(Self::Ratings),
+            "* * * * *" => Some(Self::Daily),
+            "0 0 * * 1" => Some(Self::Digest),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ratings" => Some(Self::Ratings),
+            "daily" => Some(Self::Daily),
+            "digest" => Some(Self::Digest),
+            _ => None,
+        }
+    }
+}
+
+/// Daily problems are scheduled in Korea Standard Time.
+const KST_OFFSET_MINUTES: u64 = 9 * 60;
+const MINUTES_PER_DAY: u64 = 24 * 60;
+
+struct Bot {
+    api: Api,
+    solved: solved::Client<solved::FetchTransport>,
+}
+
+impl Bot {
+    fn new(token: &str, env: &Env) -> worker::Result<Self> {
+        Ok(Self {
+            api: telbot_cf_worker::Api::new(token),
+            solved: solved::Client::from_env(env)
+                .with_cache(ResponseCache::setup(env.kv("SOLVED_CACHE")?)),
+        })
+    }
+}
+
+fn describe_request(req: &Request) -> String {
+    format!(
         "{} - [{}], located at: {:?}, within: {}",
         Date::now().to_string(),
         req.path(),
         req.cf().coordinates().unwrap_or_default(),
         req.cf().region().unwrap_or_else(|| "unknown region".into())
+    )
+}
+
+fn log_request(description: &str, cache: &CacheStats) {
+    console_log!(
+        "{}, solved.ac cache: {} hits / {} misses",
+        description,
+        cache.hits(),
+        cache.misses()
     );
 }
 
 #[event(fetch)]
 pub async fn main(req: Request, env: Env) -> worker::Result<Response> {
-    log_request(&req);
+    let description = describe_request(&req);
     utils::set_panic_hook();
     let token = env.secret("BOT_TOKEN")?.to_string();
-    let api = telbot_cf_worker::Api::new(&token);
-    let router = Router::with_data(api);
+    let bot = Bot::new(&token, &env)?;
+    let cache_stats = bot.solved.cache_stats();
+    let router = Router::with_data(bot);
     let bot_endpoint = format!("/{}", token);
-    let rating_endpoint = format!("/rating/{}", token);
-    router
+    let response = router
         .post_async(&bot_endpoint, |req, ctx| async {
             if let Err(e) = handle_request(req, ctx).await {
                 web_sys::console::error_1(&e.to_string().into());
             }
             Response::empty()
         })
-        .get_async(&rating_endpoint, |_, ctx| async move {
-            let ratings = RatingAlarms::setup(ctx.kv("RATING_ALARMS")?);
-            for subscriber in ratings.all_subscribers().await? {
-                if let Some(rating) = ratings.get_subscription(subscriber).await? {
-                    if let Some(user_info) = solved::user_show(&rating.target).await? {
-                        let new_rating = user_info.get("rating").unwrap().as_u64().unwrap();
-                        if rating.rating != new_rating {
-                            ctx.data()
-                                .send_json(&formatter::rating_update_to_message(
-                                    subscriber,
-                                    &rating.target,
-                                    rating.rating,
-                                    user_info,
-                                ))
-                                .await
-                                .map_err(convert_error)?;
-                            ratings
-                                .set_subscription(subscriber, rating.target, new_rating)
-                                .await?;
-                        }
-                    }
-                }
+        .post_async("/jobs/:job", |req, ctx| async move {
+            // Without a secret configured, jobs only run from the cron triggers
+            let secret = match ctx.secret("JOB_SECRET") {
+                Ok(secret) => secret.to_string(),
+                Err(_) => return Response::error("Not Found", 404),
+            };
+            let expected = format!("Bearer {}", secret);
+            let authorization = req.headers().get("Authorization")?;
+            if authorization.as_deref() != Some(expected.as_str()) {
+                return Response::error("Unauthorized", 401);
+            }
+            let job = match ctx.param("job").and_then(|name| Job::from_name(name)) {
+                Some(job) => job,
+                None => return Response::error("Not Found", 404),
+            };
+            if let Err(e) = run_job(job, ctx.data(), &ctx.env, Date::now().as_millis()).await {
+                return Response::error(e.to_string(), 500);
             }
             Response::empty()
         })
         .run(req, env)
-        .await
+        .await;
+    log_request(&description, &cache_stats);
+    response
+}
+
+#[event(scheduled)]
+pub async fn scheduled(event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    utils::set_panic_hook();
+    let cron = event.cron();
+    let job = match Job::from_cron(&cron) {
+        Some(job) => job,
+        None => {
+            web_sys::console::error_1(&format!("No job runs on {}", cron).into());
+            return;
+        }
+    };
+    let result = async {
+        let token = env.secret("BOT_TOKEN")?.to_string();
+        let bot = Bot::new(&token, &env)?;
+        let result = run_job(job, &bot, &env, event.schedule() as u64).await;
+        log_request(
+            &format!("Scheduled job {}", cron),
+            &bot.solved.cache_stats(),
+        );
+        result
+    };
+    if let Err(e) = result.await {
+        web_sys::console::error_1(&e.to_string().into());
+    }
+}
+
+async fn run_job(job: Job, bot: &Bot, env: &Env, now: u64) -> Result<(), Error> {
+    match job {
+        Job::Ratings => poll_subscriptions(bot, env, now).await,
+        Job::Daily => post_daily_problems(bot, env, now).await,
+        Job::Digest => send_weekly_digests(bot, env, now).await,
+    }
+}
+
+/// Sends the notifications of every subscription, and records the ratings seen.
+async fn poll_subscriptions(bot: &Bot, env: &Env, now: u64) -> Result<(), Error> {
+    let subscriptions = Subscriptions::setup(env.kv("RATING_ALARMS")?);
+    let settings = ChatSettings::setup(env.kv("CHAT_SETTINGS")?);
+    let history = RatingHistory::setup(env.kv("RATING_HISTORY")?);
+    // Chats following the same user share a single request
+    let mut users = HashMap::new();
+    let mut top_100s = HashMap::new();
+    let mut all_subscriptions = subscriptions.all_subscriptions().await?;
+    // Start where the last poll ran out of requests, so the subscriptions listed last get a turn
+    let resume_point = subscriptions.resume_point().await?;
+    if let Some(resume_point) = &resume_point {
+        let start = all_subscriptions
+            .iter()
+            .position(|(subscriber, subscription)| {
+                Subscriptions::key(*subscriber, &subscription.target) >= *resume_point
+            })
+            .unwrap_or(0);
+        all_subscriptions.rotate_left(start);
+    }
+    let mut stopped_at = None;
+    for (subscriber, mut subscription) in all_subscriptions {
+        let key = subscription.target.to_lowercase();
+        if !users.contains_key(&key) {
+            let user_info = match bot.solved.user_show_fresh(&subscription.target).await {
+                Ok(user_info) => user_info,
+                Err(SolvedError::NotFound) => continue,
+                Err(e @ SolvedError::RateLimited { .. })
+                | Err(e @ SolvedError::BudgetExhausted) => {
+                    web_sys::console::error_1(&e.to_string().into());
+                    stopped_at = Some(Subscriptions::key(subscriber, &subscription.target));
+                    break;
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e.to_string().into());
+                    continue;
+                }
+            };
+            users.insert(key.clone(), user_info);
+        }
+        let user_info = &users[&key];
+        let solved_more = subscription
+            .solved_count
+            .is_some_and(|prev| user_info.solved_count > prev);
+        if subscription.watch.problems
+            && (subscription.top_100.is_none() || solved_more)
+            && !top_100s.contains_key(&key)
+        {
+            let top_100 = match bot.solved.user_top_100_fresh(&subscription.target).await {
+                Ok(top_100) => top_100.items,
+                Err(e @ SolvedError::RateLimited { .. })
+                | Err(e @ SolvedError::BudgetExhausted) => {
+                    web_sys::console::error_1(&e.to_string().into());
+                    stopped_at = Some(Subscriptions::key(subscriber, &subscription.target));
+                    break;
+                }
+                Err(e) => {
+                    web_sys::console::error_1(&e.to_string().into());
+                    continue;
+                }
+            };
+            top_100s.insert(key.clone(), top_100);
+        }
+        let top_100 = match subscription.watch.problems {
+            true => top_100s.get(&key).map(Vec::as_slice),
+            false => None,
+        };
+        if subscription.rating != user_info.rating {
+            history
+                .record(&subscription.target, now, user_info.rating)
+                .await?;
+        }
+        let language = settings.get_language(subscriber).await?.unwrap_or_default();
+        let updates = subscription_updates(subscriber, &subscription, user_info, top_100, language);
+        // A chat that removed the bot should neither stop the others nor see the same updates again
+        for update in updates {
+            if let Err(e) = bot.api.send_json(&update).await {
+                web_sys::console::error_1(&convert_error(e).to_string().into());
+            }
+        }
+        let mut changed = subscription.update(user_info);
+        if let Some(top_100) = top_100 {
+            let ids = top_100.iter().map(|problem| problem.problem_id).collect();
+            changed |= subscription.top_100.as_ref() != Some(&ids);
+            subscription.top_100 = Some(ids);
+        }
+        if changed {
+            subscriptions
+                .set_subscription(subscriber, &subscription)
+                .await?;
+        }
+    }
+    if stopped_at != resume_point {
+        subscriptions
+            .set_resume_point(stopped_at.as_deref())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Sums up the rating changes of the last week for each chat following ratings.
+async fn send_weekly_digests(bot: &Bot, env: &Env, now: u64) -> Result<(), Error> {
+    let subscriptions = Subscriptions::setup(env.kv("RATING_ALARMS")?);
+    let settings = ChatSettings::setup(env.kv("CHAT_SETTINGS")?);
+    let history = RatingHistory::setup(env.kv("RATING_HISTORY")?);
+    let week_ago = now.saturating_sub(7 * 24 * 60 * 60 * 1000);
+    let mut histories = HashMap::new();
+    let mut digests = BTreeMap::<i64, Vec<_>>::new();
+    for (subscriber, subscription) in subscriptions.all_subscriptions().await? {
+        if !subscription.watch.ratings {
+            continue;
+        }
+        let key = subscription.target.to_lowercase();
+        if !histories.contains_key(&key) {
+            histories.insert(key.clone(), history.get(&key).await?);
+        }
+        let points = &histories[&key];
+        // The rating held when the week started, or the first one seen during it
+        let start = points
+            .iter()
+            .rev()
+            .find(|point| point.at <= week_ago)
+            .or_else(|| points.first());
+        if let (Some(start), Some(end)) = (start, points.last()) {
+            digests.entry(subscriber).or_default().push((
+                subscription.target,
+                start.rating,
+                end.rating,
+            ));
+        }
+    }
+    for (subscriber, mut changes) in digests {
+        if changes.iter().all(|(_, start, end)| start == end) {
+            continue;
+        }
+        changes.sort_by_key(|(_, start, end)| Reverse(*end as i64 - *start as i64));
+        let language = settings.get_language(subscriber).await?.unwrap_or_default();
+        let req = formatter::weekly_digest_to_message(subscriber, &changes, language);
+        // Chats that removed the bot should not keep the others from their digests
+        if let Err(e) = bot.api.send_json(&req).await {
+            web_sys::console::error_1(&convert_error(e).to_string().into());
+        }
+    }
+    Ok(())
 }
 
-async fn handle_request(mut req: Request, ctx: RouteContext<Api>) -> worker::Result<()> {
+/// Minutes since the Unix epoch, shifted to KST.
+fn kst_minutes(millis: u64) -> u64 {
+    millis / 60_000 + KST_OFFSET_MINUTES
+}
+
+/// Posts a problem to every chat whose time of the day is now, unless it already got one today.
+/// A chat that fails is logged and waits for its time on the next day, so no tick retries it.
+async fn post_daily_problems(bot: &Bot, env: &Env, now: u64) -> Result<(), Error> {
+    let daily = DailyProblems::setup(env.kv("DAILY_PROBLEMS")?);
+    let settings = ChatSettings::setup(env.kv("CHAT_SETTINGS")?);
+    let minutes = kst_minutes(now);
+    let (today, minute) = (minutes / MINUTES_PER_DAY, minutes % MINUTES_PER_DAY);
+    for chat_id in daily.chats_at(minute).await? {
+        if let Err(e) = post_daily_problem(bot, &daily, &settings, chat_id, today, now).await {
+            web_sys::console::error_1(&format!("Daily problem for {}: {}", chat_id, e).into());
+        }
+    }
+    Ok(())
+}
+
+async fn post_daily_problem(
+    bot: &Bot,
+    daily: &DailyProblems,
+    settings: &ChatSettings,
+    chat_id: i64,
+    today: u64,
+    now: u64,
+) -> Result<(), Error> {
+    let mut config = match daily.config(chat_id).await? {
+        Some(config) if config.last_day < today => config,
+        _ => return Ok(()),
+    };
+    let history = daily.history(config.chat_id).await?;
+    // A run that failed after posting leaves the post in the history but not in the setting
+    if history
+        .iter()
+        .any(|post| kst_minutes(post.at) / MINUTES_PER_DAY == today)
+    {
+        config.last_day = today;
+        daily.update_config(&config).await?;
+        return Ok(());
+    }
+    let problems = bot
+        .solved
+        .search_problem(&config.query, 1, Some(ProblemSort::Random))
+        .await?;
+    config.last_day = today;
+    daily.update_config(&config).await?;
+    // Once the query runs out of fresh problems, a repeat beats skipping the day
+    let problem = problems
+        .iter()
+        .find(|problem| {
+            history
+                .iter()
+                .all(|post| post.problem_id != problem.problem_id)
+        })
+        .or_else(|| problems.first());
+    let problem = match problem {
+        Some(problem) => problem,
+        None => return Ok(()),
+    };
+    let language = settings
+        .get_language(config.chat_id)
+        .await?
+        .unwrap_or_default();
+    let req = formatter::daily_problem_to_message(config.chat_id, problem, language);
+    let message = bot.api.send_json(&req).await.map_err(convert_error)?;
+    daily
+        .record(config.chat_id, problem.problem_id, now)
+        .await?;
+    if config.pin {
+        let pin = PinChatMessage::new(config.chat_id, message.message_id);
+        bot.api.send_json(&pin).await.map_err(convert_error)?;
+    }
+    Ok(())
+}
+
+async fn handle_request(mut req: Request, ctx: RouteContext<Bot>) -> Result<(), Error> {
+    let bot = ctx.data();
     let update = req.json::<Update>().await?;
     match update.kind {
         UpdateKind::InlineQuery { inline_query } => {
-            let (page, parity) = match inline_query.offset.parse::<u32>() {
-                Ok(page) if page > 0 => ((page + 1) / 2, (page + 1) % 2),
-                _ => (1, 0),
-            };
-            let mut result = solved::search_problem(&inline_query.query, page).await?;
-            let (result, has_next) = if parity == 1 {
-                if result.len() > 50 {
-                    (&result[50..], result.len() >= 100)
-                } else {
-                    ([].as_ref(), false)
+            // Private chats share their id with the user
+            let language = chat_language(
+                &ctx,
+                inline_query.from.id,
+                inline_query.from.language_code.as_deref(),
+            )
+            .await?;
+            handle_inline_query(inline_query, language, &ctx).await?;
+        }
+        UpdateKind::Message { message } => {
+            let language = chat_language(
+                &ctx,
+                message.chat.id,
+                message
+                    .from
+                    .as_ref()
+                    .and_then(|user| user.language_code.as_deref()),
+            )
+            .await?;
+            match handle_message(&message, language, &ctx).await {
+                Err(Error::Solved(error)) => {
+                    let reply =
+                        formatter::solved_error_to_message(message.chat.id, &error, language);
+                    bot.api.send_json(&reply).await.map_err(convert_error)?;
                 }
+                result => result?,
+            }
+        }
+        UpdateKind::CallbackQuery { callback_query } => {
+            let chat_id = match &callback_query.message {
+                Some(message) => message.chat.id,
+                None => callback_query.from.id,
+            };
+            let language =
+                chat_language(&ctx, chat_id, callback_query.from.language_code.as_deref()).await?;
+            let result = handle_callback_query(&callback_query, language, &ctx).await;
+            let mut answer = AnswerCallbackQuery::new(callback_query.id.clone());
+            if let Err(Error::Solved(error)) = &result {
+                answer = answer.with_text(formatter::solved_error_to_text(error, language));
+            }
+            bot.api.send_json(&answer).await.map_err(convert_error)?;
+            if let Err(Error::Worker(e)) = result {
+                return Err(e.into());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Prefers the language chosen with `/lang`, then the language of the user's Telegram client.
+async fn chat_language(
+    ctx: &RouteContext<Bot>,
+    chat_id: i64,
+    language_code: Option<&str>,
+) -> Result<Language, Error> {
+    let settings = ChatSettings::setup(ctx.kv("CHAT_SETTINGS")?);
+    Ok(settings
+        .get_language(chat_id)
+        .await?
+        .or_else(|| language_code.and_then(Language::from_code))
+        .unwrap_or_default())
+}
+
+async fn handle_inline_query(
+    inline_query: InlineQuery,
+    language: Language,
+    ctx: &RouteContext<Bot>,
+) -> Result<(), Error> {
+    let bot = ctx.data();
+    if let Some(keyword) = inline_query
+        .query
+        .strip_prefix('#')
+        .filter(|keyword| !keyword.contains(char::is_whitespace))
+    {
+        let tags = bot.solved.search_tag(keyword, 1).await?;
+        let response = formatter::search_tag_to_query(&tags.items, language);
+        let answer_query = AnswerInlineQuery::new(inline_query.id, response);
+        bot.api
+            .send_json(&answer_query)
+            .await
+            .map_err(convert_error)?;
+        return Ok(());
+    }
+
+    if let Some(keyword) = inline_query.query.strip_prefix('@') {
+        let users = bot.solved.search_user(keyword, 1).await?;
+        let response = formatter::search_user_to_query(&users.items, language);
+        let answer_query = AnswerInlineQuery::new(inline_query.id, response);
+        bot.api
+            .send_json(&answer_query)
+            .await
+            .map_err(convert_error)?;
+        return Ok(());
+    }
+
+    let (page, parity) = match inline_query.offset.parse::<u32>() {
+        Ok(page) if page > 0 => (page.div_ceil(2), (page + 1) % 2),
+        _ => (1, 0),
+    };
+    let mut result = bot
+        .solved
+        .search_problem(&inline_query.query, page, None)
+        .await?;
+    let (result, has_next) = if parity == 1 {
+        if result.len() > 50 {
+            (&result[50..], result.len() >= 100)
+        } else {
+            ([].as_ref(), false)
+        }
+    } else {
+        result.truncate(50);
+        (&result[..], result.len() >= 50)
+    };
+    let response = formatter::search_problem_to_query(result, language);
+    let mut answer_query = AnswerInlineQuery::new(inline_query.id, response);
+    if has_next {
+        answer_query = answer_query.with_next_offset((page * 2 + parity).to_string());
+    }
+    bot.api
+        .send_json(&answer_query)
+        .await
+        .map_err(convert_error)?;
+    Ok(())
+}
+
+async fn handle_callback_query(
+    callback_query: &CallbackQuery,
+    language: Language,
+    ctx: &RouteContext<Bot>,
+) -> Result<(), Error> {
+    let bot = ctx.data();
+    let t = language.texts();
+    let (message, data) = match (&callback_query.message, &callback_query.data) {
+        (Some(message), Some(data)) => (message, data),
+        _ => return Ok(()),
+    };
+    let (kind, payload) = data.split_once(':').unwrap_or((data, ""));
+    match kind {
+        "reroll" => {
+            let queries = RandomQueries::setup(ctx.kv("RANDOM_QUERIES")?);
+            let query = match queries.get(payload).await? {
+                Some(query) => query,
+                None => return Ok(()),
+            };
+            // Saving again keeps the buttons of queries in use from expiring
+            let query_id = queries.save(&query).await?;
+            let problems = bot
+                .solved
+                .search_problem(&query, 1, Some(ProblemSort::Random))
+                .await?;
+            if let Some(problem) = problems.first() {
+                let edit = formatter::random_problem_to_edit(
+                    message.chat.id,
+                    message.message_id,
+                    problem,
+                    &query_id,
+                    language,
+                );
+                bot.api.send_json(&edit).await.map_err(convert_error)?;
+            }
+        }
+        "class" => {
+            let user = bot.solved.user_show(payload).await?;
+            send_class_essentials(bot, message.chat.id, &user, language).await?;
+        }
+        "top100" => {
+            if let Some((handle, page)) = payload.rsplit_once(':') {
+                let page = page.parse().unwrap_or(0);
+                let top_100 = bot.solved.user_top_100(handle).await?;
+                let edit = formatter::top_100_to_edit(
+                    message.chat.id,
+                    message.message_id,
+                    handle,
+                    &top_100.items,
+                    page,
+                    language,
+                );
+                bot.api.send_json(&edit).await.map_err(convert_error)?;
+            }
+        }
+        "tag" => {
+            let query = format!("#{}", payload);
+            let mut problems = bot
+                .solved
+                .search_problem(&query, 1, Some(ProblemSort::Level))
+                .await?;
+            problems.truncate(20);
+            let req = if problems.is_empty() {
+                SendMessage::new(message.chat.id, t.problem_not_found)
             } else {
-                result.truncate(50);
-                (&result[..], result.len() >= 50)
+                formatter::problem_show_to_message(message.chat.id, &problems, language)
             };
-            let response = formatter::search_problem_to_query(result);
-            let mut answer_query = AnswerInlineQuery::new(inline_query.id, response);
-            if has_next {
-                answer_query = answer_query.with_next_offset((page * 2 + parity).to_string());
-            }
-            ctx.data()
-                .send_json(&answer_query)
-                .await
-                .map_err(convert_error)?;
+            bot.api.send_json(&req).await.map_err(convert_error)?;
         }
-        UpdateKind::Message { message } => {
-            if let Some(text) = message.text() {
-                let command = Command::new(text);
-                let mut args = command.args();
-                match command.label {
-                    "/problem" => match args.map(str::parse).collect::<Result<Vec<_>, _>>() {
-                        Ok(id_list) if !id_list.is_empty() => {
-                            let problems = solved::problem_lookup(&id_list).await?;
-                            if problems.is_empty() {
-                                let req =
-                                    SendMessage::new(message.chat.id, "문제를 찾을 수 없습니다.");
-                                ctx.data().send_json(&req).await.map_err(convert_error)?;
-                            } else {
-                                let req =
-                                    formatter::problem_show_to_message(message.chat.id, &problems);
-                                ctx.data().send_json(&req).await.map_err(convert_error)?;
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn handle_message(
+    message: &Message,
+    language: Language,
+    ctx: &RouteContext<Bot>,
+) -> Result<(), Error> {
+    let bot = ctx.data();
+    let t = language.texts();
+    if let Some(text) = message.text() {
+        let command = Command::new(text);
+        let mut args = command.args();
+        match command.label {
+            "/problem" => match args.map(str::parse).collect::<Result<Vec<_>, _>>() {
+                Ok(id_list) if !id_list.is_empty() => {
+                    let problems = bot.solved.problem_lookup(&id_list).await?;
+                    if problems.is_empty() {
+                        let req = SendMessage::new(message.chat.id, t.problem_not_found);
+                        bot.api.send_json(&req).await.map_err(convert_error)?;
+                    } else if let [problem] = problems.as_slice() {
+                        let req = formatter::problem_detail_to_message(
+                            message.chat.id,
+                            problem,
+                            language,
+                        );
+                        bot.api.send_json(&req).await.map_err(convert_error)?;
+                    } else {
+                        let req = formatter::problem_show_to_message(
+                            message.chat.id,
+                            &problems,
+                            language,
+                        );
+                        bot.api.send_json(&req).await.map_err(convert_error)?;
+                    }
+                }
+                _ => {
+                    let req = SendMessage::new(message.chat.id, t.usage_problem);
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                }
+            },
+            "/user" => {
+                if let Some(handle) = handle_or_linked(ctx, message, args.next()).await? {
+                    match bot.solved.user_show(&handle).await {
+                        Ok(user) => {
+                            let images = ProfileImages::setup(ctx.kv("PROFILE_IMAGES")?);
+                            let key = card::cache_key(&user);
+                            let card = images.get_id(&key).await?;
+                            let req = formatter::user_show_to_message(
+                                message.chat.id,
+                                user,
+                                card.map(Into::into),
+                                language,
+                            )
+                            .await?;
+                            let message = bot.api.send_file(&req).await.map_err(convert_error)?;
+                            // Telegram lists every size it made of the photo, the original last
+                            if let MessageKind::Photo { photo, .. } = message.kind {
+                                if let Some(size) = photo.last() {
+                                    images.set_id(&key, &size.file_id).await?;
+                                }
                             }
                         }
-                        _ => {
-                            let req = SendMessage::new(
-                                message.chat.id,
-                                "사용법: /problem <문제번호1> <문제번호2> <...>",
-                            );
-                            ctx.data().send_json(&req).await.map_err(convert_error)?;
+                        Err(SolvedError::NotFound) => {
+                            let req = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_user);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/get" => {
+                if let Some(reply_to) = message.reply_to_message.as_deref().and_then(Message::text)
+                {
+                    let regex = RegExp::new(r"(\d+)번?", "g");
+                    let mut problems = vec![];
+                    while let Some(captures) = regex.exec(reply_to) {
+                        let number = Number::from(captures.get(1)).value_of() as u32;
+                        problems.push(number);
+                    }
+                    if !problems.is_empty() {
+                        let problems = bot.solved.problem_lookup(&problems).await?;
+                        let request = formatter::problem_show_to_message(
+                            message.chat.id,
+                            &problems,
+                            language,
+                        )
+                        .reply_to(message.message_id);
+                        bot.api.send_json(&request).await.map_err(convert_error)?;
+                    }
+                }
+            }
+            "/random" => {
+                let args = args.collect::<Vec<_>>();
+                let linked = if args.contains(&"for:") {
+                    match linked_handle(ctx, message).await? {
+                        Some(handle) => Some(handle),
+                        None => {
+                            let req = SendMessage::new(message.chat.id, t.not_linked);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                            return Ok(());
                         }
+                    }
+                } else {
+                    None
+                };
+                let query = random_query(args.into_iter(), linked.as_deref());
+                let problems = bot
+                    .solved
+                    .search_problem(&query, 1, Some(ProblemSort::Random))
+                    .await?;
+                let req = match problems.first() {
+                    Some(problem) => {
+                        let query_id = RandomQueries::setup(ctx.kv("RANDOM_QUERIES")?)
+                            .save(&query)
+                            .await?;
+                        formatter::random_problem_to_message(
+                            message.chat.id,
+                            problem,
+                            &query_id,
+                            language,
+                        )
+                    }
+                    None => SendMessage::new(message.chat.id, t.no_matching_problem),
+                };
+                bot.api.send_json(&req).await.map_err(convert_error)?;
+            }
+            "/tag" => {
+                if let Some(keyword) = args.next() {
+                    let keyword = keyword.trim_start_matches('#');
+                    let mut tags = bot.solved.search_tag(keyword, 1).await?.items;
+                    // Prefer an exact match over whatever solved.ac ranks first
+                    if let Some(index) = tags.iter().position(|tag| {
+                        tag.key == keyword || tag.aliases.iter().any(|alias| alias.alias == keyword)
+                    }) {
+                        tags.swap(0, index);
+                    }
+                    let req = match tags.split_first() {
+                        Some((tag, others)) => {
+                            formatter::tag_to_message(message.chat.id, tag, others, language)
+                        }
+                        None => SendMessage::new(message.chat.id, t.tag_not_found),
+                    };
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                } else {
+                    let tags = bot.solved.tag_list(1).await?.items;
+                    let req = formatter::tag_list_to_message(message.chat.id, &tags, language);
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                }
+            }
+            "/class" => {
+                if let Some(handle) = args.next() {
+                    let user = match bot.solved.user_show(handle).await {
+                        Ok(user) => user,
+                        Err(SolvedError::NotFound) => {
+                            let req = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    if args.next() == Some("essentials") {
+                        send_class_essentials(bot, message.chat.id, &user, language).await?;
+                    } else {
+                        let stats = bot.solved.user_class_stats(handle).await?;
+                        let req = formatter::class_stats_to_message(
+                            message.chat.id,
+                            &user,
+                            &stats,
+                            language,
+                        );
+                        bot.api.send_json(&req).await.map_err(convert_error)?;
+                    }
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_class);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/stats" => {
+                if let Some(handle) = handle_or_linked(ctx, message, args.next()).await? {
+                    let stats = match bot.solved.user_problem_stats(&handle).await {
+                        Ok(stats) => stats,
+                        Err(SolvedError::NotFound) => {
+                            let req = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    let chart = chart::render_level_histogram(&stats);
+                    let png = chart::encode_png(&chart)
+                        .map_err(|e| worker::Error::RustError(format!("Image Error: {}", e)))?;
+                    let req = formatter::problem_stats_to_message(
+                        message.chat.id,
+                        &handle,
+                        &stats,
+                        png,
+                        language,
+                    );
+                    bot.api.send_file(&req).await.map_err(convert_error)?;
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_stats);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/graph" => {
+                let args = args.collect::<Vec<_>>();
+                let lone_period = matches!(args.as_slice(), [arg] if parse_period(arg).is_some());
+                let linked = if args.is_empty() || lone_period {
+                    linked_handle(ctx, message).await?
+                } else {
+                    None
+                };
+                // Handles may look like periods, so a lone period names the handle unless one is
+                // linked; `/graph 30d 1y` reaches the handle `30d` either way
+                let (handle, period) = match (args.as_slice(), linked) {
+                    ([period], Some(linked)) if lone_period => (Some(linked), Some(*period)),
+                    ([handle, period, ..], _) => (Some(handle.to_string()), Some(*period)),
+                    ([handle], _) => (Some(handle.to_string()), None),
+                    ([], linked) => (linked, None),
+                };
+                let period = period.map(parse_period);
+                match (handle, period) {
+                    (Some(handle), None) | (Some(handle), Some(Some(_))) => {
+                        let history = RatingHistory::setup(ctx.kv("RATING_HISTORY")?);
+                        let points = history.get(&handle).await?;
+                        let now = Date::now().as_millis();
+                        let from = match (period.flatten(), points.first()) {
+                            (Some(period), _) => now.saturating_sub(period),
+                            (None, Some(first)) => first.at,
+                            (None, None) => now,
+                        };
+                        // Keep the last point before the period, as the rating it started with
+                        let start = points
+                            .iter()
+                            .rposition(|point| point.at <= from)
+                            .unwrap_or(0);
+                        let points = points[start..]
+                            .iter()
+                            .map(|point| (point.at, point.rating))
+                            .collect::<Vec<_>>();
+                        if points.is_empty() {
+                            let req = SendMessage::new(message.chat.id, t.graph_empty);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        let chart = chart::render_rating_history(&points, from, now);
+                        let png = chart::encode_png(&chart)
+                            .map_err(|e| worker::Error::RustError(format!("Image Error: {}", e)))?;
+                        let req = formatter::rating_graph_to_message(
+                            message.chat.id,
+                            &handle,
+                            png,
+                            language,
+                        );
+                        bot.api.send_file(&req).await.map_err(convert_error)?;
+                    }
+                    _ => {
+                        let help = SendMessage::new(message.chat.id, t.usage_graph);
+                        bot.api.send_json(&help).await.map_err(convert_error)?;
+                    }
+                }
+            }
+            "/top100" => {
+                if let Some(handle) = args.next() {
+                    let top_100 = match bot.solved.user_top_100(handle).await {
+                        Ok(top_100) => top_100,
+                        Err(SolvedError::NotFound) => {
+                            let req = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    let req = formatter::top_100_to_message(
+                        message.chat.id,
+                        handle,
+                        &top_100.items,
+                        language,
+                    );
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_top_100);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/org" => {
+                let query = args.collect::<Vec<_>>().join(" ");
+                if query.is_empty() {
+                    let help = SendMessage::new(message.chat.id, t.usage_org);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                    return Ok(());
+                }
+                let organization = match query.parse() {
+                    Ok(id) => match bot.solved.organization_show(id).await {
+                        Ok(organization) => Some(organization),
+                        Err(SolvedError::NotFound) => None,
+                        Err(e) => return Err(e.into()),
                     },
-                    "/user" => {
-                        if let Some(handle) = args.next() {
-                            let user = solved::user_show(handle).await?;
-                            if let Some(user) = user {
-                                let images = ProfileImages::setup(ctx.kv("PROFILE_IMAGES")?);
-                                let profile = images.get_id(handle).await?;
-                                let req = formatter::user_show_to_message(
+                    Err(_) => bot
+                        .solved
+                        .search_organization(&query, 1)
+                        .await?
+                        .items
+                        .into_iter()
+                        .next(),
+                };
+                if let Some(organization) = organization {
+                    let mut members = bot
+                        .solved
+                        .user_ranking(UserRanking::InOrganization(organization.organization_id), 1)
+                        .await?
+                        .items;
+                    members.truncate(10);
+                    let req = formatter::organization_to_message(
+                        message.chat.id,
+                        &organization,
+                        &members,
+                        language,
+                    );
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                } else {
+                    let req = SendMessage::new(message.chat.id, t.organization_not_found);
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                }
+            }
+            "/ranking" => {
+                let mut ranking = Some(UserRanking::Tier);
+                let mut page = args.next();
+                match page {
+                    Some("tier") => page = args.next(),
+                    Some("class") => {
+                        ranking = Some(UserRanking::Class);
+                        page = args.next();
+                    }
+                    Some("streak") => {
+                        ranking = Some(UserRanking::Streak);
+                        page = args.next();
+                    }
+                    Some("org") => {
+                        page = args.next();
+                        ranking = page
+                            .and_then(|id| id.parse().ok())
+                            .map(UserRanking::InOrganization);
+                        if ranking.is_some() {
+                            page = args.next();
+                        }
+                    }
+                    _ => {}
+                }
+                let page = match page.map(str::parse::<u32>) {
+                    None => 1,
+                    Some(Ok(page)) if page > 0 => page,
+                    _ => {
+                        let help = SendMessage::new(message.chat.id, t.usage_ranking);
+                        bot.api.send_json(&help).await.map_err(convert_error)?;
+                        return Ok(());
+                    }
+                };
+                // solved.ac returns 100 entries per page, which is too long for one message
+                let (api_page, skip) = (page.div_ceil(2), (page + 1) % 2 * 50);
+                let offset = (page as u64 - 1) * 50;
+                let req = if let Some(ranking) = ranking {
+                    let users = bot.solved.user_ranking(ranking, api_page).await?.items;
+                    let users = users
+                        .into_iter()
+                        .skip(skip as usize)
+                        .take(50)
+                        .collect::<Vec<_>>();
+                    formatter::user_ranking_to_message(
+                        message.chat.id,
+                        ranking,
+                        &users,
+                        offset,
+                        language,
+                    )
+                } else {
+                    let organizations = bot.solved.organization_ranking(api_page).await?.items;
+                    let organizations = organizations
+                        .into_iter()
+                        .skip(skip as usize)
+                        .take(50)
+                        .collect::<Vec<_>>();
+                    formatter::organization_ranking_to_message(
+                        message.chat.id,
+                        &organizations,
+                        offset,
+                        language,
+                    )
+                };
+                bot.api.send_json(&req).await.map_err(convert_error)?;
+            }
+            "/arena" => {
+                if let Some(handle) = args.next() {
+                    match bot.solved.user_show(handle).await {
+                        Ok(user) => {
+                            let req = formatter::arena_to_message(message.chat.id, &user, language);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                        }
+                        Err(SolvedError::NotFound) => {
+                            let error = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&error).await.map_err(convert_error)?;
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_arena);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/link" => {
+                if let (Some(handle), Some(from)) = (args.next(), &message.from) {
+                    match bot.solved.user_show(handle).await {
+                        Ok(user) => {
+                            let handles = LinkedHandles::setup(ctx.kv("LINKED_HANDLES")?);
+                            // Relinking the same handle keeps its verification
+                            let verified = handles.get(from.id).await?.is_some_and(|linked| {
+                                linked.verified && linked.handle == user.handle
+                            });
+                            handles.link(from.id, &user.handle, verified).await?;
+                            let success =
+                                SendMessage::new(message.chat.id, fill(t.linked, &[&user.handle]));
+                            bot.api.send_json(&success).await.map_err(convert_error)?;
+                        }
+                        Err(SolvedError::NotFound) => {
+                            let req = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_link);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/unlink" => {
+                if let Some(from) = &message.from {
+                    let handles = LinkedHandles::setup(ctx.kv("LINKED_HANDLES")?);
+                    handles.unlink(from.id).await?;
+                    let success = SendMessage::new(message.chat.id, t.unlinked);
+                    bot.api.send_json(&success).await.map_err(convert_error)?;
+                }
+            }
+            "/verify" => {
+                if let Some(from) = &message.from {
+                    let handles = LinkedHandles::setup(ctx.kv("LINKED_HANDLES")?);
+                    let arg = args.next();
+                    match handles.get_pending(from.id).await? {
+                        Some(pending) if arg.is_none_or(|handle| handle == pending.handle) => {
+                            let user = bot.solved.user_show_fresh(&pending.handle).await?;
+                            let req = if user.bio.contains(&pending.token) {
+                                handles.link(from.id, &user.handle, true).await?;
+                                handles.clear_pending(from.id).await?;
+                                SendMessage::new(message.chat.id, fill(t.verified, &[&user.handle]))
+                            } else {
+                                SendMessage::new(
                                     message.chat.id,
-                                    user,
-                                    profile.clone().map(Into::into),
+                                    fill(t.verification_failed, &[&pending.token]),
                                 )
-                                .await?;
-                                let message =
-                                    ctx.data().send_file(&req).await.map_err(convert_error)?;
-                                if let MessageKind::Document { document, .. } = message.kind {
-                                    images.set_id(handle, &document.file_id).await?;
-                                }
-                            } else {
-                                let req =
-                                    SendMessage::new(message.chat.id, "사용자를 찾을 수 없습니다.");
-                                ctx.data().send_json(&req).await.map_err(convert_error)?;
+                            };
+                            bot.api.send_json(&req).await.map_err(convert_error)?;
+                        }
+                        _ => match handle_or_linked(ctx, message, arg).await? {
+                            Some(handle) => {
+                                let user = match bot.solved.user_show(&handle).await {
+                                    Ok(user) => user,
+                                    Err(SolvedError::NotFound) => {
+                                        let req =
+                                            SendMessage::new(message.chat.id, t.user_not_found);
+                                        bot.api.send_json(&req).await.map_err(convert_error)?;
+                                        return Ok(());
+                                    }
+                                    Err(e) => return Err(e.into()),
+                                };
+                                let pending = PendingVerification {
+                                    token: verification_token(),
+                                    handle: user.handle,
+                                };
+                                handles.set_pending(from.id, &pending).await?;
+                                let req = SendMessage::new(
+                                    message.chat.id,
+                                    fill(
+                                        t.verification_started,
+                                        &[
+                                            &formatter::escape_markdown_v2(&pending.handle),
+                                            &pending.token,
+                                        ],
+                                    ),
+                                )
+                                .disable_web_page_preview()
+                                .with_parse_mode(ParseMode::MarkdownV2);
+                                bot.api.send_json(&req).await.map_err(convert_error)?;
+                            }
+                            None => {
+                                let help = SendMessage::new(message.chat.id, t.usage_verify);
+                                bot.api.send_json(&help).await.map_err(convert_error)?;
+                            }
+                        },
+                    }
+                }
+            }
+            "/lang" => match args.next().and_then(Language::from_code) {
+                Some(language) => {
+                    let settings = ChatSettings::setup(ctx.kv("CHAT_SETTINGS")?);
+                    settings.set_language(message.chat.id, language).await?;
+                    let success =
+                        SendMessage::new(message.chat.id, language.texts().language_changed);
+                    bot.api.send_json(&success).await.map_err(convert_error)?;
+                }
+                None => {
+                    let help = SendMessage::new(message.chat.id, t.usage_lang);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            },
+            "/join" => {
+                let user_id = match &message.from {
+                    Some(user) => user.id,
+                    None => return Ok(()),
+                };
+                if let Some(handle) = handle_or_linked(ctx, message, args.next()).await? {
+                    let user = match bot.solved.user_show(&handle).await {
+                        Ok(user) => user,
+                        Err(SolvedError::NotFound) => {
+                            let error = SendMessage::new(message.chat.id, t.user_not_found);
+                            bot.api.send_json(&error).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    let settings = ChatSettings::setup(ctx.kv("CHAT_SETTINGS")?);
+                    if settings.get_verified_only(message.chat.id).await? {
+                        let verified = LinkedHandles::setup(ctx.kv("LINKED_HANDLES")?)
+                            .get(user_id)
+                            .await?
+                            .is_some_and(|linked| {
+                                linked.verified && linked.handle.eq_ignore_ascii_case(&user.handle)
+                            });
+                        if !verified {
+                            let error = SendMessage::new(message.chat.id, t.verification_required);
+                            bot.api.send_json(&error).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                    }
+                    let members = ChatMembers::setup(ctx.kv("CHAT_MEMBERS")?);
+                    let handle = formatter::escape_markdown_v2(&user.handle);
+                    let reply = match members
+                        .join(message.chat.id, user_id, &user.handle, MAX_MEMBERS)
+                        .await?
+                    {
+                        Join::Joined => {
+                            SendMessage::new(message.chat.id, fill(t.joined, &[&handle]))
+                                .with_parse_mode(ParseMode::MarkdownV2)
+                        }
+                        Join::Taken => {
+                            SendMessage::new(message.chat.id, fill(t.handle_taken, &[&handle]))
+                                .with_parse_mode(ParseMode::MarkdownV2)
+                        }
+                        Join::Full => {
+                            SendMessage::new(message.chat.id, fill(t.members_full, &[&MAX_MEMBERS]))
+                        }
+                    };
+                    bot.api.send_json(&reply).await.map_err(convert_error)?;
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_join);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/verifiedonly" => {
+                let verified_only = match args.next() {
+                    Some("on") => true,
+                    Some("off") => false,
+                    _ => {
+                        let help = SendMessage::new(message.chat.id, t.usage_verified_only);
+                        bot.api.send_json(&help).await.map_err(convert_error)?;
+                        return Ok(());
+                    }
+                };
+                let admin = match &message.from {
+                    Some(user) => is_admin(bot, message.chat.id, user.id).await?,
+                    None => false,
+                };
+                if !admin {
+                    let error = SendMessage::new(message.chat.id, t.admin_required);
+                    bot.api.send_json(&error).await.map_err(convert_error)?;
+                    return Ok(());
+                }
+                ChatSettings::setup(ctx.kv("CHAT_SETTINGS")?)
+                    .set_verified_only(message.chat.id, verified_only)
+                    .await?;
+                let text = if verified_only {
+                    t.verified_only_on
+                } else {
+                    t.verified_only_off
+                };
+                let reply = SendMessage::new(message.chat.id, text);
+                bot.api.send_json(&reply).await.map_err(convert_error)?;
+            }
+            "/leave" => {
+                let user_id = match &message.from {
+                    Some(user) => user.id,
+                    None => return Ok(()),
+                };
+                let members = ChatMembers::setup(ctx.kv("CHAT_MEMBERS")?);
+                let removed = match args.next() {
+                    Some(handle) => {
+                        if !is_admin(bot, message.chat.id, user_id).await? {
+                            let error = SendMessage::new(message.chat.id, t.admin_only);
+                            bot.api.send_json(&error).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        members
+                            .remove(message.chat.id, |member| {
+                                member.handle.eq_ignore_ascii_case(handle)
+                            })
+                            .await?
+                    }
+                    None => {
+                        members
+                            .remove(message.chat.id, |member| member.user_id == user_id)
+                            .await?
+                    }
+                };
+                let text = match removed.first() {
+                    Some(handle) => fill(t.left, &[&formatter::escape_markdown_v2(handle)]),
+                    None => t.not_member.to_string(),
+                };
+                let reply =
+                    SendMessage::new(message.chat.id, text).with_parse_mode(ParseMode::MarkdownV2);
+                bot.api.send_json(&reply).await.map_err(convert_error)?;
+            }
+            "/leaderboard" => {
+                let leaderboard = match args.next() {
+                    Some(arg) => Leaderboard::from_arg(arg),
+                    None => Some(Leaderboard::Rating),
+                };
+                if let Some(leaderboard) = leaderboard {
+                    let members = ChatMembers::setup(ctx.kv("CHAT_MEMBERS")?)
+                        .get(message.chat.id)
+                        .await?;
+                    let linked_handles = LinkedHandles::setup(ctx.kv("LINKED_HANDLES")?);
+                    let mut verified = HashSet::new();
+                    for member in &members {
+                        if let Some(linked) = linked_handles.get(member.user_id).await? {
+                            if linked.verified && linked.handle.eq_ignore_ascii_case(&member.handle)
+                            {
+                                verified.insert(member.handle.to_lowercase());
                             }
-                        } else {
-                            let help =
-                                SendMessage::new(message.chat.id, "사용법: /user <사용자명>");
-                            ctx.data().send_json(&help).await.map_err(convert_error)?;
                         }
                     }
-                    "/get" => {
-                        if let Some(reply_to) =
-                            message.reply_to_message.as_deref().and_then(Message::text)
-                        {
-                            let regex = RegExp::new(r"(\d+)번?", "g");
-                            let mut problems = vec![];
-                            while let Some(captures) = regex.exec(reply_to) {
-                                let number = Number::from(captures.get(1)).value_of() as u32;
-                                problems.push(number);
+                    let handles = members
+                        .into_iter()
+                        .map(|member| member.handle)
+                        .collect::<Vec<_>>();
+                    let mut users = bot.solved.user_show_batch(&handles).await?;
+                    leaderboard.sort(&mut users);
+                    let req = formatter::leaderboard_to_message(
+                        message.chat.id,
+                        leaderboard,
+                        &users,
+                        &verified,
+                        language,
+                    );
+                    bot.api.send_json(&req).await.map_err(convert_error)?;
+                } else {
+                    let help = SendMessage::new(message.chat.id, t.usage_leaderboard);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            }
+            "/daily" => {
+                let daily = DailyProblems::setup(ctx.kv("DAILY_PROBLEMS")?);
+                let args = args.collect::<Vec<_>>();
+                let user_id = message.from.as_ref().map(|user| user.id);
+                match args.split_first() {
+                    None => {
+                        let text = match daily.config(message.chat.id).await? {
+                            Some(config) => formatter::daily_config_to_text(&config, language),
+                            None => t.daily_not_set.to_string(),
+                        };
+                        let reply = SendMessage::new(message.chat.id, text);
+                        bot.api.send_json(&reply).await.map_err(convert_error)?;
+                    }
+                    Some((&"on", rest)) | Some((&"off", rest)) => {
+                        let admin = match user_id {
+                            Some(user_id) => is_admin(bot, message.chat.id, user_id).await?,
+                            None => false,
+                        };
+                        if !admin {
+                            let error = SendMessage::new(message.chat.id, t.admin_required);
+                            bot.api.send_json(&error).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        if args[0] == "off" {
+                            let text = if daily.remove_config(message.chat.id).await? {
+                                t.daily_off
+                            } else {
+                                t.daily_not_set
+                            };
+                            let reply = SendMessage::new(message.chat.id, text);
+                            bot.api.send_json(&reply).await.map_err(convert_error)?;
+                            return Ok(());
+                        }
+                        let (pin, rest) = match rest.split_last() {
+                            Some((&"pin", rest)) => (true, rest),
+                            _ => (false, rest),
+                        };
+                        let config = match rest.split_last() {
+                            Some((time, query)) if !query.is_empty() => {
+                                parse_time(time).map(|minute| {
+                                    let now = kst_minutes(Date::now().as_millis());
+                                    let today = now / MINUTES_PER_DAY;
+                                    DailyConfig {
+                                        chat_id: message.chat.id,
+                                        query: random_query(query.iter().copied(), None),
+                                        minute,
+                                        pin,
+                                        // A time already past today starts tomorrow
+                                        last_day: if now % MINUTES_PER_DAY >= minute {
+                                            today
+                                        } else {
+                                            today - 1
+                                        },
+                                    }
+                                })
                             }
-                            if !problems.is_empty() {
-                                let problems = solved::problem_lookup(&problems).await?;
-                                let request =
-                                    formatter::problem_show_to_message(message.chat.id, &problems)
-                                        .reply_to(message.message_id);
-                                ctx.data()
-                                    .send_json(&request)
-                                    .await
-                                    .map_err(convert_error)?;
+                            _ => None,
+                        };
+                        let config = match config {
+                            Some(config) => config,
+                            None => {
+                                let help = SendMessage::new(message.chat.id, t.usage_daily);
+                                bot.api.send_json(&help).await.map_err(convert_error)?;
+                                return Ok(());
                             }
+                        };
+                        let problems = bot
+                            .solved
+                            .search_problem(&config.query, 1, Some(ProblemSort::Random))
+                            .await?;
+                        if problems.is_empty() {
+                            let error = SendMessage::new(message.chat.id, t.no_matching_problem);
+                            bot.api.send_json(&error).await.map_err(convert_error)?;
+                            return Ok(());
                         }
+                        let reply = SendMessage::new(
+                            message.chat.id,
+                            formatter::daily_config_to_text(&config, language),
+                        );
+                        daily.set_config(&config).await?;
+                        bot.api.send_json(&reply).await.map_err(convert_error)?;
+                    }
+                    _ => {
+                        let help = SendMessage::new(message.chat.id, t.usage_daily);
+                        bot.api.send_json(&help).await.map_err(convert_error)?;
+                    }
+                }
+            }
+            "/subscribe" => {
+                let kind = args.next();
+                let args = args.collect::<Vec<_>>();
+                let lone_step = kind == Some("solved")
+                    && matches!(args.as_slice(), [arg] if parse_step(arg).is_some());
+                let linked = if args.is_empty() || lone_step {
+                    linked_handle(ctx, message).await?
+                } else {
+                    None
+                };
+                // Handles may be numbers, so a lone step names the handle unless one is linked;
+                // `/subscribe solved 1234 100` reaches the handle `1234` either way
+                let (handle, step) = match (args.as_slice(), linked) {
+                    ([step], Some(linked)) if lone_step => (Some(linked), Some(*step)),
+                    ([handle, step, ..], _) => (Some(handle.to_string()), Some(*step)),
+                    ([handle], _) => (Some(handle.to_string()), None),
+                    ([], linked) => (linked, None),
+                };
+                let handle = match kind {
+                    Some("ratings" | "tier" | "class" | "solved" | "problems") => handle,
+                    _ => None,
+                };
+                let solved_step = match step {
+                    Some(step) => parse_step(step),
+                    None => Some(DEFAULT_SOLVED_STEP),
+                };
+                match (kind, handle, solved_step) {
+                    (Some(kind), Some(handle), Some(solved_step)) => {
+                        // The snapshot is what the poll compares against, so it must not be stale
+                        let user = match bot.solved.user_show_fresh(&handle).await {
+                            Ok(user) => user,
+                            Err(SolvedError::NotFound) => {
+                                let error = SendMessage::new(message.chat.id, t.user_not_found);
+                                bot.api.send_json(&error).await.map_err(convert_error)?;
+                                return Ok(());
+                            }
+                            Err(e) => return Err(e.into()),
+                        };
+                        let subscriptions = Subscriptions::setup(ctx.kv("RATING_ALARMS")?);
+                        // Existing subscriptions keep their snapshot so pending changes still notify
+                        let mut subscription = subscriptions
+                            .get_subscription(message.chat.id, &user.handle)
+                            .await?
+                            .unwrap_or_else(|| Subscription::new(&user));
+                        let template = match kind {
+                            "ratings" => {
+                                subscription.watch.ratings = true;
+                                t.subscribed
+                            }
+                            "tier" => {
+                                subscription.watch.tier = true;
+                                t.subscribed_tier
+                            }
+                            "class" => {
+                                subscription.watch.class = true;
+                                t.subscribed_class
+                            }
+                            // The poll records the top 100 to compare against on its first run
+                            "problems" => {
+                                subscription.watch.problems = true;
+                                t.subscribed_problems
+                            }
+                            _ => {
+                                subscription.watch.solved_step = Some(solved_step);
+                                t.subscribed_solved
+                            }
+                        };
+                        subscriptions
+                            .set_subscription(message.chat.id, &subscription)
+                            .await?;
+                        let history = RatingHistory::setup(ctx.kv("RATING_HISTORY")?);
+                        history
+                            .record(&user.handle, Date::now().as_millis(), user.rating)
+                            .await?;
+
+                        let success = SendMessage::new(
+                            message.chat.id,
+                            fill(
+                                template,
+                                &[&formatter::escape_markdown_v2(&user.handle), &solved_step],
+                            ),
+                        )
+                        .with_parse_mode(ParseMode::MarkdownV2);
+                        bot.api.send_json(&success).await.map_err(convert_error)?;
+                    }
+                    _ => {
+                        let help = SendMessage::new(message.chat.id, t.usage_subscribe);
+                        bot.api.send_json(&help).await.map_err(convert_error)?;
                     }
-                    "/subscribe" => match args.next() {
-                        Some("ratings") => {
-                            if let Some(handle) = args.next() {
-                                let ratings = RatingAlarms::setup(ctx.kv("RATING_ALARMS")?);
-                                if let Some(user) = solved::user_show(handle).await? {
-                                    ratings
-                                        .set_subscription(
-                                            message.chat.id,
-                                            handle,
-                                            user.get("rating").and_then(Value::as_u64).unwrap(),
-                                        )
-                                        .await?;
-
-                                    let success = SendMessage::new(
-                                        message.chat.id,
-                                        format!("*{}*님의 레이팅 변화를 구독했습니다\\.", handle),
-                                    )
-                                    .with_parse_mode(ParseMode::MarkdownV2);
-                                    ctx.data()
-                                        .send_json(&success)
-                                        .await
-                                        .map_err(convert_error)?;
-                                } else {
-                                    let error = SendMessage::new(
-                                        message.chat.id,
-                                        "사용자를 찾을 수 없습니다.",
-                                    );
-                                    ctx.data().send_json(&error).await.map_err(convert_error)?;
+                }
+            }
+            "/unsubscribe" => match (args.next(), args.next()) {
+                (
+                    Some(kind @ ("ratings" | "tier" | "class" | "solved" | "problems" | "all")),
+                    Some(handle),
+                ) => {
+                    let subscriptions = Subscriptions::setup(ctx.kv("RATING_ALARMS")?);
+                    let subscription = subscriptions
+                        .get_subscription(message.chat.id, handle)
+                        .await?;
+                    let text = match subscription {
+                        Some(mut subscription) => {
+                            let watch = &mut subscription.watch;
+                            let watching = match kind {
+                                "ratings" => mem::replace(&mut watch.ratings, false),
+                                "tier" => mem::replace(&mut watch.tier, false),
+                                "class" => mem::replace(&mut watch.class, false),
+                                "solved" => watch.solved_step.take().is_some(),
+                                "problems" => {
+                                    subscription.top_100 = None;
+                                    mem::replace(&mut watch.problems, false)
                                 }
+                                _ => !mem::take(watch).is_empty(),
+                            };
+                            subscriptions
+                                .set_subscription(message.chat.id, &subscription)
+                                .await?;
+                            if watching {
+                                t.unsubscribed
                             } else {
-                                let help = SendMessage::new(
-                                    message.chat.id,
-                                    "사용법: /subscribe ratings <사용자명>",
-                                );
-                                ctx.data().send_json(&help).await.map_err(convert_error)?;
+                                t.not_subscribed
                             }
                         }
-                        _ => {
-                            let help = SendMessage::new(
-                                message.chat.id,
-                                "사용법: /subscribe ratings <사용자명>",
-                            );
-                            ctx.data().send_json(&help).await.map_err(convert_error)?;
-                        }
-                    },
-                    "/unsubscribe" => match args.next() {
-                        Some("ratings") => {
-                            let ratings = RatingAlarms::setup(ctx.kv("RATING_ALARMS")?);
-                            ratings.unsubscribe(message.chat.id).await?;
-                            let success = SendMessage::new(
-                                message.chat.id,
-                                "레이팅 변화 구독이 취소되었습니다.",
-                            );
-                            ctx.data()
-                                .send_json(&success)
-                                .await
-                                .map_err(convert_error)?;
-                        }
-                        _ => {
-                            let help =
-                                SendMessage::new(message.chat.id, "사용법: /unsubscribe ratings");
-                            ctx.data().send_json(&help).await.map_err(convert_error)?;
-                        }
-                    },
-                    _ => {}
+                        None => t.not_subscribed,
+                    };
+                    let reply = SendMessage::new(
+                        message.chat.id,
+                        fill(text, &[&formatter::escape_markdown_v2(handle)]),
+                    )
+                    .with_parse_mode(ParseMode::MarkdownV2);
+                    bot.api.send_json(&reply).await.map_err(convert_error)?;
                 }
+                _ => {
+                    let help = SendMessage::new(message.chat.id, t.usage_unsubscribe);
+                    bot.api.send_json(&help).await.map_err(convert_error)?;
+                }
+            },
+            "/subscriptions" => {
+                let subscriptions = Subscriptions::setup(ctx.kv("RATING_ALARMS")?)
+                    .subscriptions(message.chat.id)
+                    .await?;
+                let reply =
+                    formatter::subscriptions_to_message(message.chat.id, &subscriptions, language);
+                bot.api.send_json(&reply).await.map_err(convert_error)?;
             }
+            _ => {}
         }
-        _ => {}
     }
     Ok(())
 }
 
+/// Collects the notifications for what changed since the poll last saw the user.
+///
+/// Fields missing from older subscriptions start being watched from the current value.
+fn subscription_updates(
+    subscriber: i64,
+    subscription: &Subscription,
+    user: &solved::User,
+    top_100: Option<&[solved::Problem]>,
+    language: Language,
+) -> Vec<SendMessage> {
+    let handle = &subscription.target;
+    subscription
+        .changes(user, top_100)
+        .into_iter()
+        .map(|change| match change {
+            Change::Rating { prev } => {
+                formatter::rating_update_to_message(subscriber, handle, prev, user)
+            }
+            Change::ArenaRating { prev } => {
+                formatter::arena_rating_update_to_message(subscriber, handle, prev, user, language)
+            }
+            Change::Tier { prev } => {
+                formatter::tier_update_to_message(subscriber, handle, prev, user, language)
+            }
+            Change::Class => formatter::class_update_to_message(subscriber, handle, user, language),
+            Change::Solved(count) => {
+                formatter::solved_milestone_to_message(subscriber, handle, count, language)
+            }
+            Change::NewSolves { solves, more } => {
+                formatter::new_solves_to_message(subscriber, handle, &solves, more, language)
+            }
+        })
+        .collect()
+}
+
+async fn is_admin(bot: &Bot, chat_id: i64, user_id: i64) -> Result<bool, Error> {
+    // Private chats share their id with the user, who runs the chat
+    if chat_id == user_id {
+        return Ok(true);
+    }
+    let member = bot
+        .api
+        .send_json(&GetChatMember::new(chat_id, user_id))
+        .await
+        .map_err(convert_error)?;
+    Ok(matches!(
+        member,
+        ChatMember::Creator { .. } | ChatMember::Administrator { .. }
+    ))
+}
+
+/// Uses the argument if given, and the handle linked by the sender otherwise.
+async fn handle_or_linked(
+    ctx: &RouteContext<Bot>,
+    message: &Message,
+    arg: Option<&str>,
+) -> Result<Option<String>, Error> {
+    match arg {
+        Some(handle) => Ok(Some(handle.to_string())),
+        None => linked_handle(ctx, message).await,
+    }
+}
+
+async fn linked_handle(
+    ctx: &RouteContext<Bot>,
+    message: &Message,
+) -> Result<Option<String>, Error> {
+    let user_id = match &message.from {
+        Some(user) => user.id,
+        None => return Ok(None),
+    };
+    let handles = LinkedHandles::setup(ctx.kv("LINKED_HANDLES")?);
+    Ok(handles.get(user_id).await?.map(|linked| linked.handle))
+}
+
+/// Lists the essential problems of the class after the user's current one that are still unsolved.
+async fn send_class_essentials(
+    bot: &Bot,
+    chat_id: i64,
+    user: &solved::User,
+    language: Language,
+) -> Result<(), Error> {
+    let class = (user.class + 1).min(10);
+    let query = format!("in_class_essentials:{} -s@{}", class, user.handle);
+    let problems = bot
+        .solved
+        .search_problem(&query, 1, Some(ProblemSort::Level))
+        .await?;
+    let req =
+        formatter::class_essentials_to_message(chat_id, &user.handle, class, &problems, language);
+    bot.api.send_json(&req).await.map_err(convert_error)?;
+    Ok(())
+}
+
+fn verification_token() -> String {
+    let random = (Math::random() * u32::MAX as f64) as u32;
+    format!("solvedacbot-{:08x}", random)
+}
+
+enum Error {
+    Solved(SolvedError),
+    Worker(worker::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Solved(e) => write!(f, "{}", e),
+            Error::Worker(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<SolvedError> for Error {
+    fn from(e: SolvedError) -> Self {
+        Error::Solved(e)
+    }
+}
+
+impl From<worker::Error> for Error {
+    fn from(e: worker::Error) -> Self {
+        Error::Worker(e)
+    }
+}
+
+impl From<kv::KvError> for Error {
+    fn from(e: kv::KvError) -> Self {
+        Error::Worker(e.into())
+    }
+}
+
 fn convert_error(error: telbot_cf_worker::Error) -> worker::Error {
     match error {
         telbot_cf_worker::Error::TelegramError(e) => {
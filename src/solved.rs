@@ -1,61 +1,337 @@
-use serde::Deserialize;
-use serde_json::{Map, Value};
-use worker::js_sys::encode_uri;
-use worker::{Fetch, Method, Request};
-
-macro_rules! api_url {
-    ($endpoint:literal) => {
-        concat!("https://solved.ac/api/v3", $endpoint)
-    };
-}
-
-pub async fn search_problem(query: &str, page: u32) -> worker::Result<Vec<Map<String, Value>>> {
-    let query = encode_uri(query);
-    let url = format!(
-        concat!(api_url!("/search/problem"), "?query={}&page={}"),
-        query, page
-    );
-    let request = Request::new(&url, Method::Get)?;
-    let mut response = Fetch::Request(request).send().await?;
-
-    #[derive(Deserialize)]
-    struct RawResponse {
-        items: Vec<Map<String, Value>>,
-    }
-
-    if response.status_code() == 200 {
-        let raw: RawResponse = response.json().await?;
-        Ok(raw.items)
-    } else {
-        Ok(vec![])
-    }
-}
-
-pub async fn problem_lookup(id_list: &[u32]) -> worker::Result<Vec<Map<String, Value>>> {
-    let ids: String = id_list.iter().map(|id| format!(",{}", id)).collect();
-    let url = format!(
-        concat!(api_url!("/problem/lookup"), "?problemIds={}"),
-        &ids[1..]
-    );
-    let request = Request::new(&url, Method::Get)?;
-    let mut response = Fetch::Request(request).send().await?;
-    if response.status_code() == 200 {
-        response.json().await
-    } else {
-        Ok(vec![])
-    }
-}
-
-pub async fn user_show(handle: &str) -> worker::Result<Option<Map<String, Value>>> {
-    let handle = encode_uri(handle);
-    let url = format!(concat!(api_url!("/user/show"), "?handle={}"), handle);
-    let request = Request::new(&url, Method::Get)?;
-    let mut response = Fetch::Request(request).send().await?;
-
-    if response.status_code() == 200 {
-        let value = response.json().await?;
-        Ok(Some(value))
-    } else {
-        Ok(None)
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use std::cell::Cell;
+use std::rc::Rc;
+use worker::Env;
+
+mod cache;
+mod error;
+mod model;
+mod policy;
+#[cfg(test)]
+mod tests;
+mod transport;
+
+pub use cache::{Cache, CacheStats};
+pub use error::SolvedError;
+pub use model::*;
+pub use policy::RequestPolicy;
+pub use transport::{FetchTransport, HttpResponse, Transport};
+
+pub const DEFAULT_BASE_URL: &str = "https://solved.ac/api/v3";
+
+const SEARCH_TTL: u64 = 60 * 10;
+const PROBLEM_TTL: u64 = 60 * 60;
+const USER_TTL: u64 = 60 * 5;
+const TAG_TTL: u64 = 60 * 60 * 24;
+
+#[derive(Clone, Copy)]
+enum Caching {
+    /// Serve from the cache, storing fresh responses for the given seconds.
+    Use(u64),
+    Skip,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProblemSort {
+    Id,
+    Level,
+    Title,
+    Solved,
+    AverageTry,
+    Random,
+}
+
+impl ProblemSort {
+    fn as_str(self) -> &'static str {
+        match self {
+            ProblemSort::Id => "id",
+            ProblemSort::Level => "level",
+            ProblemSort::Title => "title",
+            ProblemSort::Solved => "solved",
+            ProblemSort::AverageTry => "average_try",
+            ProblemSort::Random => "random",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UserRanking {
+    Tier,
+    Class,
+    Streak,
+    InOrganization(u64),
+}
+
+pub struct Client<T> {
+    transport: T,
+    base_url: String,
+    policy: RequestPolicy,
+    remaining: Cell<u32>,
+    cache: Option<Box<dyn Cache>>,
+    cache_stats: Rc<CacheStats>,
+}
+
+impl Client<FetchTransport> {
+    /// Uses `SOLVED_API_URL` as the base URL if it is set, and reads the request policy from
+    /// the environment.
+    pub fn from_env(env: &Env) -> Self {
+        let base_url = env
+            .var("SOLVED_API_URL")
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Self::new(FetchTransport, base_url).with_policy(RequestPolicy::from_env(env))
+    }
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if base_url.ends_with('/') {
+            base_url.pop();
+        }
+        let policy = RequestPolicy::default();
+        Self {
+            transport,
+            base_url,
+            policy,
+            remaining: Cell::new(policy.budget),
+            cache: None,
+            cache_stats: Default::default(),
+        }
     }
+
+    pub fn with_policy(mut self, policy: RequestPolicy) -> Self {
+        self.policy = policy;
+        self.remaining.set(policy.budget);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(Box::new(cache));
+        self
+    }
+
+    pub fn cache_stats(&self) -> Rc<CacheStats> {
+        self.cache_stats.clone()
+    }
+
+    async fn get<R: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &str,
+        caching: Caching,
+    ) -> Result<R, SolvedError> {
+        let path = format!("{}?{}", endpoint, query);
+        let cache = self.cache.as_ref();
+        let ttl = match (cache, caching) {
+            (Some(cache), Caching::Use(ttl)) => {
+                let cached = cache.get(&path).await;
+                if let Some(value) = cached.and_then(|body| serde_json::from_str(&body).ok()) {
+                    self.cache_stats.hit();
+                    return Ok(value);
+                }
+                self.cache_stats.miss();
+                Some(ttl)
+            }
+            _ => None,
+        };
+
+        let body = self.fetch(&path).await?;
+        let value = serde_json::from_slice(&body)?;
+        if let (Some(cache), Some(ttl)) = (cache, ttl) {
+            if let Ok(body) = String::from_utf8(body) {
+                cache.put(&path, body, ttl).await;
+            }
+        }
+        Ok(value)
+    }
+
+    async fn fetch(&self, path: &str) -> Result<Vec<u8>, SolvedError> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut attempt = 0;
+        loop {
+            let result = self.send(&url).await;
+            let delay = match &result {
+                Err(SolvedError::RateLimited {
+                    retry_after: Some(seconds),
+                }) => seconds.saturating_mul(1000),
+                Err(SolvedError::RateLimited { retry_after: None })
+                | Err(SolvedError::Transport(_)) => self.policy.backoff(attempt),
+                Err(SolvedError::Upstream(status)) if *status >= 500 => {
+                    self.policy.backoff(attempt)
+                }
+                _ => return result,
+            };
+            if attempt >= self.policy.max_retries || delay > self.policy.max_delay {
+                return result;
+            }
+            self.transport.sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send(&self, url: &str) -> Result<Vec<u8>, SolvedError> {
+        let remaining = self.remaining.get();
+        if remaining == 0 {
+            return Err(SolvedError::BudgetExhausted);
+        }
+        self.remaining.set(remaining - 1);
+
+        let response = self.transport.get(url).await?;
+        match response.status {
+            200 => Ok(response.body),
+            404 => Err(SolvedError::NotFound),
+            429 => Err(SolvedError::RateLimited {
+                retry_after: response.retry_after,
+            }),
+            status => Err(SolvedError::Upstream(status)),
+        }
+    }
+
+    /// Sorts by solved.ac's default order if `sort` is `None`. Random searches are never cached.
+    pub async fn search_problem(
+        &self,
+        query: &str,
+        page: u32,
+        sort: Option<ProblemSort>,
+    ) -> Result<Vec<Problem>, SolvedError> {
+        let mut query = format!("query={}&page={}", encode(query), page);
+        if let Some(sort) = sort {
+            query.push_str("&sort=");
+            query.push_str(sort.as_str());
+        }
+        let caching = if sort == Some(ProblemSort::Random) {
+            Caching::Skip
+        } else {
+            Caching::Use(SEARCH_TTL)
+        };
+        let page: SearchPage<Problem> = self.get("/search/problem", &query, caching).await?;
+        Ok(page.items)
+    }
+
+    pub async fn problem_lookup(&self, id_list: &[u32]) -> Result<Vec<Problem>, SolvedError> {
+        let ids: String = id_list.iter().map(|id| format!(",{}", id)).collect();
+        let query = format!("problemIds={}", encode(&ids[1..]));
+        self.get("/problem/lookup", &query, Caching::Use(PROBLEM_TTL))
+            .await
+    }
+
+    pub async fn search_tag(&self, query: &str, page: u32) -> Result<SearchPage<Tag>, SolvedError> {
+        let query = format!("query={}&page={}", encode(query), page);
+        self.get("/search/tag", &query, Caching::Use(TAG_TTL)).await
+    }
+
+    pub async fn tag_list(&self, page: u32) -> Result<SearchPage<Tag>, SolvedError> {
+        let query = format!("page={}", page);
+        self.get("/tag/list", &query, Caching::Use(TAG_TTL)).await
+    }
+
+    pub async fn search_user(
+        &self,
+        query: &str,
+        page: u32,
+    ) -> Result<SearchPage<User>, SolvedError> {
+        let query = format!("query={}&page={}", encode(query), page);
+        self.get("/search/user", &query, Caching::Use(SEARCH_TTL))
+            .await
+    }
+
+    pub async fn user_show(&self, handle: &str) -> Result<User, SolvedError> {
+        let query = format!("handle={}", encode(handle));
+        self.get("/user/show", &query, Caching::Use(USER_TTL)).await
+    }
+
+    pub async fn user_class_stats(&self, handle: &str) -> Result<Vec<ClassStat>, SolvedError> {
+        let query = format!("handle={}", encode(handle));
+        self.get("/user/class_stats", &query, Caching::Use(USER_TTL))
+            .await
+    }
+
+    pub async fn user_problem_stats(&self, handle: &str) -> Result<Vec<ProblemStat>, SolvedError> {
+        let query = format!("handle={}", encode(handle));
+        self.get("/user/problem_stats", &query, Caching::Use(USER_TTL))
+            .await
+    }
+
+    pub async fn user_top_100(&self, handle: &str) -> Result<SearchPage<Problem>, SolvedError> {
+        let query = format!("handle={}", encode(handle));
+        self.get("/user/top_100", &query, Caching::Use(USER_TTL))
+            .await
+    }
+
+    pub async fn organization_show(&self, id: u64) -> Result<Organization, SolvedError> {
+        let query = format!("organizationId={}", id);
+        self.get("/organization/show", &query, Caching::Use(USER_TTL))
+            .await
+    }
+
+    pub async fn search_organization(
+        &self,
+        query: &str,
+        page: u32,
+    ) -> Result<SearchPage<Organization>, SolvedError> {
+        let query = format!("query={}&page={}", encode(query), page);
+        self.get("/search/organization", &query, Caching::Use(SEARCH_TTL))
+            .await
+    }
+
+    pub async fn organization_ranking(
+        &self,
+        page: u32,
+    ) -> Result<SearchPage<Organization>, SolvedError> {
+        let query = format!("page={}", page);
+        self.get("/ranking/organization", &query, Caching::Use(USER_TTL))
+            .await
+    }
+
+    pub async fn user_ranking(
+        &self,
+        ranking: UserRanking,
+        page: u32,
+    ) -> Result<SearchPage<User>, SolvedError> {
+        let (endpoint, query) = match ranking {
+            UserRanking::Tier => ("/ranking/tier", format!("page={}", page)),
+            UserRanking::Class => ("/ranking/class", format!("page={}", page)),
+            UserRanking::Streak => ("/ranking/streak", format!("page={}", page)),
+            UserRanking::InOrganization(id) => (
+                "/ranking/in_organization",
+                format!("organizationId={}&page={}", id, page),
+            ),
+        };
+        self.get(endpoint, &query, Caching::Use(USER_TTL)).await
+    }
+
+    /// Looks up the users one after another, leaving out those solved.ac does not know.
+    pub async fn user_show_batch(&self, handles: &[String]) -> Result<Vec<User>, SolvedError> {
+        let mut users = Vec::with_capacity(handles.len());
+        for handle in handles {
+            match self.user_show(handle).await {
+                Ok(user) => users.push(user),
+                Err(SolvedError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(users)
+    }
+
+    /// Same as [`Client::user_show`], but always asks solved.ac and leaves the cache alone, as
+    /// the poller reads these every few minutes and never from the cache.
+    pub async fn user_show_fresh(&self, handle: &str) -> Result<User, SolvedError> {
+        let query = format!("handle={}", encode(handle));
+        self.get("/user/show", &query, Caching::Skip).await
+    }
+
+    /// Same as [`Client::user_top_100`], but always asks solved.ac and leaves the cache alone.
+    pub async fn user_top_100_fresh(
+        &self,
+        handle: &str,
+    ) -> Result<SearchPage<Problem>, SolvedError> {
+        let query = format!("handle={}", encode(handle));
+        self.get("/user/top_100", &query, Caching::Skip).await
+    }
+}
+
+fn encode(s: &str) -> String {
+    utf8_percent_encode(s, NON_ALPHANUMERIC).to_string()
 }
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use std::cell::Cell;
+
+/// Stores raw solved.ac responses. Failures are expected to be swallowed, since a broken cache
+/// should only make the bot slower.
+#[async_trait(?Send)]
+pub trait Cache {
+    async fn get(&self, key: &str) -> Option<String>;
+
+    async fn put(&self, key: &str, value: String, ttl: u64);
+}
+
+#[derive(Default)]
+pub struct CacheStats {
+    hits: Cell<u32>,
+    misses: Cell<u32>,
+}
+
+impl CacheStats {
+    pub fn hits(&self) -> u32 {
+        self.hits.get()
+    }
+
+    pub fn misses(&self) -> u32 {
+        self.misses.get()
+    }
+
+    pub(super) fn hit(&self) {
+        self.hits.set(self.hits.get() + 1);
+    }
+
+    pub(super) fn miss(&self) {
+        self.misses.set(self.misses.get() + 1);
+    }
+}
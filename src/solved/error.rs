@@ -0,0 +1,50 @@
+use std::fmt;
+
+pub enum SolvedError {
+    NotFound,
+    RateLimited {
+        retry_after: Option<u64>,
+    },
+    Upstream(u16),
+    Decode(serde_json::Error),
+    Transport(worker::Error),
+    /// The per-invocation request budget has been used up.
+    BudgetExhausted,
+}
+
+impl fmt::Display for SolvedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolvedError::NotFound => write!(f, "solved.ac: not found"),
+            SolvedError::RateLimited {
+                retry_after: Some(seconds),
+            } => write!(f, "solved.ac: rate limited, retry after {}s", seconds),
+            SolvedError::RateLimited { retry_after: None } => write!(f, "solved.ac: rate limited"),
+            SolvedError::Upstream(status) => write!(f, "solved.ac: HTTP {}", status),
+            SolvedError::Decode(e) => write!(f, "solved.ac: invalid response ({})", e),
+            SolvedError::Transport(e) => write!(f, "solved.ac: {}", e),
+            SolvedError::BudgetExhausted => write!(f, "solved.ac: request budget exhausted"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SolvedError {
+    fn from(e: serde_json::Error) -> Self {
+        SolvedError::Decode(e)
+    }
+}
+
+impl From<worker::Error> for SolvedError {
+    fn from(e: worker::Error) -> Self {
+        SolvedError::Transport(e)
+    }
+}
+
+impl From<SolvedError> for worker::Error {
+    fn from(e: SolvedError) -> Self {
+        match e {
+            SolvedError::Transport(e) => e,
+            e => worker::Error::RustError(e.to_string()),
+        }
+    }
+}
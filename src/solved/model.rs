@@ -0,0 +1,155 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct SearchPage<T> {
+    pub count: u64,
+    pub items: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    pub problem_id: u32,
+    pub title_ko: String,
+    #[serde(default)]
+    pub titles: Vec<ProblemTitle>,
+    pub is_solvable: bool,
+    pub is_partial: bool,
+    pub accepted_user_count: u64,
+    pub level: u64,
+    pub voted_user_count: u64,
+    #[serde(default)]
+    pub sprout: bool,
+    #[serde(default)]
+    pub gives_no_rating: bool,
+    #[serde(default)]
+    pub is_level_locked: bool,
+    pub average_tries: f64,
+    #[serde(default)]
+    pub official: bool,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemTitle {
+    pub language: String,
+    pub language_display_name: String,
+    pub title: String,
+    pub is_original: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub key: String,
+    pub is_meta: bool,
+    pub boj_tag_id: u64,
+    pub problem_count: u64,
+    pub display_names: Vec<TagDisplayName>,
+    #[serde(default)]
+    pub aliases: Vec<TagAlias>,
+}
+
+#[derive(Deserialize)]
+pub struct TagDisplayName {
+    pub language: String,
+    pub name: String,
+    pub short: String,
+}
+
+#[derive(Deserialize)]
+pub struct TagAlias {
+    pub alias: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub handle: String,
+    #[serde(default)]
+    pub bio: String,
+    pub badge_id: Option<String>,
+    pub background_id: Option<String>,
+    pub profile_image_url: Option<String>,
+    pub solved_count: u64,
+    pub vote_count: u64,
+    pub class: u64,
+    pub class_decoration: ClassDecoration,
+    pub rival_count: u64,
+    #[serde(default)]
+    pub reverse_rival_count: u64,
+    pub tier: u64,
+    pub rating: u64,
+    pub rating_by_problems_sum: u64,
+    pub rating_by_class: u64,
+    pub rating_by_solved_count: u64,
+    pub rating_by_vote_count: u64,
+    #[serde(default)]
+    pub arena_tier: u64,
+    #[serde(default)]
+    pub arena_rating: u64,
+    #[serde(default)]
+    pub arena_max_tier: u64,
+    #[serde(default)]
+    pub arena_max_rating: u64,
+    #[serde(default)]
+    pub arena_competed_round_count: u64,
+    #[serde(default)]
+    pub max_streak: u64,
+    #[serde(default)]
+    pub coins: u64,
+    #[serde(default)]
+    pub stardusts: u64,
+    pub joined_at: Option<String>,
+    pub banned_until: Option<String>,
+    pub pro_until: Option<String>,
+    pub rank: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    pub organization_id: u64,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub rating: u64,
+    pub user_count: u64,
+    pub vote_count: u64,
+    pub solved_count: u64,
+    pub color: Option<String>,
+    pub rank: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassStat {
+    pub class: u64,
+    pub total: u64,
+    pub total_solved: u64,
+    pub essentials: u64,
+    pub essential_solved: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ProblemStat {
+    pub level: u64,
+    pub total: u64,
+    pub solved: u64,
+    #[serde(default)]
+    pub partial: u64,
+    #[serde(default)]
+    pub tried: u64,
+    #[serde(default)]
+    pub exp: u64,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassDecoration {
+    None,
+    Silver,
+    Gold,
+}
@@ -0,0 +1,51 @@
+use std::str::FromStr;
+use worker::Env;
+
+/// Controls how often a client may call solved.ac and how it backs off on failures.
+#[derive(Clone, Copy)]
+pub struct RequestPolicy {
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds, doubled for every following one.
+    pub base_delay: u64,
+    /// Longest delay the client is willing to wait in milliseconds, including `Retry-After`.
+    pub max_delay: u64,
+    /// Number of requests a single client may send, retries included.
+    pub budget: u32,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: 500,
+            max_delay: 5000,
+            budget: 40,
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// Overrides the defaults with `SOLVED_MAX_RETRIES`, `SOLVED_BACKOFF_MS`,
+    /// `SOLVED_MAX_BACKOFF_MS` and `SOLVED_REQUEST_BUDGET` if they are set.
+    pub fn from_env(env: &Env) -> Self {
+        let default = Self::default();
+        Self {
+            max_retries: var(env, "SOLVED_MAX_RETRIES").unwrap_or(default.max_retries),
+            base_delay: var(env, "SOLVED_BACKOFF_MS").unwrap_or(default.base_delay),
+            max_delay: var(env, "SOLVED_MAX_BACKOFF_MS").unwrap_or(default.max_delay),
+            budget: var(env, "SOLVED_REQUEST_BUDGET").unwrap_or(default.budget),
+        }
+    }
+
+    pub fn backoff(&self, attempt: u32) -> u64 {
+        self.base_delay
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_delay)
+    }
+}
+
+fn var<T: FromStr>(env: &Env, name: &str) -> Option<T> {
+    env.var(name)
+        .ok()
+        .and_then(|value| value.to_string().parse().ok())
+}
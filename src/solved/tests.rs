@@ -0,0 +1,255 @@
+use async_trait::async_trait;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use super::*;
+
+const USER_SHOW: &str = include_str!("../../tests/fixtures/user_show.json");
+const SEARCH_PROBLEM: &str = include_str!("../../tests/fixtures/search_problem.json");
+
+/// Answers with canned responses in order, and records what the client asked for.
+#[derive(Default)]
+struct MockTransport {
+    responses: RefCell<VecDeque<HttpResponse>>,
+    requests: RefCell<Vec<String>>,
+    sleeps: RefCell<Vec<u64>>,
+}
+
+impl MockTransport {
+    fn respond(self, status: u16, retry_after: Option<u64>, body: &str) -> Self {
+        self.responses.borrow_mut().push_back(HttpResponse {
+            status,
+            retry_after,
+            body: body.as_bytes().to_vec(),
+        });
+        self
+    }
+}
+
+#[async_trait(?Send)]
+impl Transport for MockTransport {
+    async fn get(&self, url: &str) -> worker::Result<HttpResponse> {
+        self.requests.borrow_mut().push(url.to_string());
+        let response = self.responses.borrow_mut().pop_front();
+        Ok(response.expect("no response left for the request"))
+    }
+
+    async fn sleep(&self, millis: u64) {
+        self.sleeps.borrow_mut().push(millis);
+    }
+}
+
+#[derive(Clone, Default)]
+struct MemoryCache {
+    entries: Rc<RefCell<HashMap<String, String>>>,
+}
+
+#[async_trait(?Send)]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.entries.borrow().get(key).cloned()
+    }
+
+    async fn put(&self, key: &str, value: String, _ttl: u64) {
+        self.entries.borrow_mut().insert(key.to_string(), value);
+    }
+}
+
+/// Nothing here ever waits, so a single poll finishes every future.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future is waiting on nothing"),
+    }
+}
+
+fn ok<T>(result: Result<T, SolvedError>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn client(transport: MockTransport) -> Client<MockTransport> {
+    Client::new(transport, "https://solved.ac/api/v3/")
+}
+
+#[test]
+fn decodes_user() {
+    let client = client(MockTransport::default().respond(200, None, USER_SHOW));
+    let user = ok(block_on(client.user_show("kiwiyou")));
+    assert_eq!(
+        *client.transport.requests.borrow(),
+        ["https://solved.ac/api/v3/user/show?handle=kiwiyou"]
+    );
+    assert_eq!(user.handle, "kiwiyou");
+    assert_eq!(user.tier, 24);
+    assert_eq!(user.rating, 2513);
+    assert_eq!(user.class, 7);
+    assert!(user.class_decoration == ClassDecoration::Gold);
+    assert_eq!(user.max_streak, 101);
+    // Arena fields are missing for users who never competed
+    assert_eq!(user.arena_rating, 0);
+}
+
+#[test]
+fn decodes_problems_with_tags() {
+    let client = client(MockTransport::default().respond(200, None, SEARCH_PROBLEM));
+    let problems = ok(block_on(client.search_problem(
+        "a+b",
+        1,
+        Some(ProblemSort::Id),
+    )));
+    assert_eq!(
+        *client.transport.requests.borrow(),
+        ["https://solved.ac/api/v3/search/problem?query=a%2Bb&page=1&sort=id"]
+    );
+    assert_eq!(problems.len(), 2);
+    let tags = &problems[0].tags;
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[0].key, "arithmetic");
+    assert_eq!(tags[0].display_names[1].name, "arithmetic");
+    assert_eq!(tags[0].aliases[0].alias, "더하기");
+    assert!(tags[1].aliases.is_empty());
+    assert!(problems[0].sprout && problems[0].is_level_locked);
+    assert!(problems[1].tags.is_empty() && problems[1].titles.is_empty());
+}
+
+#[test]
+fn reports_malformed_responses() {
+    let client = client(MockTransport::default().respond(200, None, "{\"handle\":1}"));
+    let result = block_on(client.user_show("kiwiyou"));
+    assert!(matches!(result, Err(SolvedError::Decode(_))));
+}
+
+#[test]
+fn retries_server_errors_with_backoff() {
+    let transport = MockTransport::default()
+        .respond(503, None, "")
+        .respond(502, None, "")
+        .respond(200, None, USER_SHOW);
+    let client = client(transport);
+    ok(block_on(client.user_show("kiwiyou")));
+    assert_eq!(client.transport.requests.borrow().len(), 3);
+    assert_eq!(*client.transport.sleeps.borrow(), [500, 1000]);
+}
+
+#[test]
+fn gives_up_after_max_retries() {
+    let transport = MockTransport::default()
+        .respond(500, None, "")
+        .respond(500, None, "")
+        .respond(500, None, "");
+    let client = client(transport);
+    let result = block_on(client.user_show("kiwiyou"));
+    assert!(matches!(result, Err(SolvedError::Upstream(500))));
+    assert_eq!(client.transport.requests.borrow().len(), 3);
+}
+
+#[test]
+fn does_not_retry_client_errors() {
+    let client = client(MockTransport::default().respond(404, None, ""));
+    let result = block_on(client.user_show("nobody"));
+    assert!(matches!(result, Err(SolvedError::NotFound)));
+    assert!(client.transport.sleeps.borrow().is_empty());
+}
+
+#[test]
+fn waits_for_retry_after() {
+    let transport = MockTransport::default()
+        .respond(429, Some(2), "")
+        .respond(429, None, "")
+        .respond(200, None, USER_SHOW);
+    let client = client(transport);
+    ok(block_on(client.user_show("kiwiyou")));
+    assert_eq!(*client.transport.sleeps.borrow(), [2000, 1000]);
+}
+
+#[test]
+fn gives_up_when_retry_after_exceeds_max_delay() {
+    let client = client(MockTransport::default().respond(429, Some(60), ""));
+    let result = block_on(client.user_show("kiwiyou"));
+    assert!(matches!(
+        result,
+        Err(SolvedError::RateLimited {
+            retry_after: Some(60)
+        })
+    ));
+    assert!(client.transport.sleeps.borrow().is_empty());
+}
+
+#[test]
+fn stops_when_budget_is_exhausted() {
+    let transport = MockTransport::default()
+        .respond(200, None, USER_SHOW)
+        .respond(503, None, "");
+    let client = client(transport).with_policy(RequestPolicy {
+        budget: 2,
+        ..Default::default()
+    });
+    ok(block_on(client.user_show("kiwiyou")));
+    // The retry of the second request is the one over the budget
+    let result = block_on(client.user_show("kiwiyou"));
+    assert!(matches!(result, Err(SolvedError::BudgetExhausted)));
+    let result = block_on(client.user_show("kiwiyou"));
+    assert!(matches!(result, Err(SolvedError::BudgetExhausted)));
+    assert_eq!(client.transport.requests.borrow().len(), 2);
+}
+
+#[test]
+fn serves_cached_responses() {
+    let cache = MemoryCache::default();
+    let client =
+        client(MockTransport::default().respond(200, None, USER_SHOW)).with_cache(cache.clone());
+    ok(block_on(client.user_show("kiwiyou")));
+    let user = ok(block_on(client.user_show("kiwiyou")));
+    assert_eq!(user.handle, "kiwiyou");
+    assert_eq!(client.transport.requests.borrow().len(), 1);
+    assert!(cache
+        .entries
+        .borrow()
+        .contains_key("/user/show?handle=kiwiyou"));
+    let stats = client.cache_stats();
+    assert_eq!((stats.hits(), stats.misses()), (1, 1));
+}
+
+#[test]
+fn fresh_lookups_bypass_cache() {
+    let cache = MemoryCache::default();
+    let stale = USER_SHOW.replace("2513", "2400");
+    cache
+        .entries
+        .borrow_mut()
+        .insert("/user/show?handle=kiwiyou".to_string(), stale.clone());
+    let client =
+        client(MockTransport::default().respond(200, None, USER_SHOW)).with_cache(cache.clone());
+    let user = ok(block_on(client.user_show_fresh("kiwiyou")));
+    assert_eq!(user.rating, 2513);
+    assert_eq!(client.transport.requests.borrow().len(), 1);
+    // The poller never reads these back, so they are not worth a write
+    assert_eq!(cache.entries.borrow()["/user/show?handle=kiwiyou"], stale);
+    let stats = client.cache_stats();
+    assert_eq!((stats.hits(), stats.misses()), (0, 0));
+}
+
+#[test]
+fn skips_cache_for_random_searches() {
+    let cache = MemoryCache::default();
+    let transport = MockTransport::default()
+        .respond(200, None, SEARCH_PROBLEM)
+        .respond(200, None, SEARCH_PROBLEM);
+    let client = client(transport).with_cache(cache.clone());
+    for _ in 0..2 {
+        ok(block_on(client.search_problem(
+            "*s",
+            1,
+            Some(ProblemSort::Random),
+        )));
+    }
+    assert_eq!(client.transport.requests.borrow().len(), 2);
+    assert!(cache.entries.borrow().is_empty());
+    let stats = client.cache_stats();
+    assert_eq!((stats.hits(), stats.misses()), (0, 0));
+}
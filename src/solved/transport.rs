@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use worker::js_sys::{Function, Promise};
+use worker::{Fetch, Method, Request};
+
+pub struct HttpResponse {
+    pub status: u16,
+    /// `Retry-After` in seconds, if the server sent one.
+    pub retry_after: Option<u64>,
+    pub body: Vec<u8>,
+}
+
+#[async_trait(?Send)]
+pub trait Transport {
+    async fn get(&self, url: &str) -> worker::Result<HttpResponse>;
+
+    async fn sleep(&self, millis: u64);
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &Function, timeout: f64) -> JsValue;
+}
+
+/// Sends requests through the Workers runtime.
+pub struct FetchTransport;
+
+#[async_trait(?Send)]
+impl Transport for FetchTransport {
+    async fn get(&self, url: &str) -> worker::Result<HttpResponse> {
+        let request = Request::new(url, Method::Get)?;
+        let mut response = Fetch::Request(request).send().await?;
+        let retry_after = response
+            .headers()
+            .get("Retry-After")?
+            .and_then(|value| value.trim().parse().ok());
+        Ok(HttpResponse {
+            status: response.status_code(),
+            retry_after,
+            body: response.bytes().await?,
+        })
+    }
+
+    async fn sleep(&self, millis: u64) {
+        let timer = Promise::new(&mut |resolve, _| {
+            set_timeout(&resolve, millis as f64);
+        });
+        let _ = JsFuture::from(timer).await;
+    }
+}